@@ -0,0 +1,574 @@
+use crate::url::Url;
+
+/// A compiled list of Adblock Plus style network filters.
+///
+/// Supported syntax:
+/// * `||ads.example.com^` anchors the pattern to the host name or one of its subdomains.
+/// * `|https://` and `.gif|` anchor the pattern to the start or the end of the address.
+/// * `*` matches any sequence of characters, `^` matches a separator character or the end of the address.
+/// * `@@` turns a filter into an exception.
+/// * Options `$third-party`, `$~third-party`, `$domain=a.com|~b.com`, `$match-case` and `$important`.
+///
+/// Comments, element hiding rules, regular expression filters and filters with other options are skipped.
+///
+/// # Example
+/// ```rust
+/// use url_parse::adblock::{FilterList, FilterMatch};
+/// use url_parse::core::Parser;
+/// let list = FilterList::parse("||ads.example.com^\n@@||ads.example.com/allowed/");
+/// let parser = Parser::new(None);
+/// let request = parser.parse("https://ads.example.com/banner.png").unwrap();
+/// match list.check(&request, None) {
+///     FilterMatch::Blocked(filter) => assert_eq!(filter.raw, "||ads.example.com^"),
+///     _ => panic!(),
+/// }
+/// let request = parser.parse("https://ads.example.com/allowed/banner.png").unwrap();
+/// assert!(matches!(list.check(&request, None), FilterMatch::Allowed { .. }));
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct FilterList {
+    pub filters: Vec<Filter>,
+}
+
+/// A single network filter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Filter {
+    /// The filter as written in the list.
+    pub raw: String,
+    pub exception: bool,
+    pub important: bool,
+    pub match_case: bool,
+    /// `Some(true)` for `$third-party`, `Some(false)` for `$~third-party`.
+    pub third_party: Option<bool>,
+    pub include_domains: Vec<String>,
+    pub exclude_domains: Vec<String>,
+    anchor: Anchor,
+    anchor_end: bool,
+    tokens: Vec<Token>,
+}
+
+/// The outcome of checking a request against a `FilterList`.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FilterMatch<'a> {
+    NoMatch,
+    Blocked(&'a Filter),
+    Allowed {
+        exception: &'a Filter,
+        blocked_by: &'a Filter,
+    },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Anchor {
+    None,
+    Start,
+    Domain,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+enum Token {
+    Literal(Vec<u8>),
+    Wildcard,
+    Separator,
+}
+
+/// The request address prepared for matching.
+struct Request {
+    text: String,
+    host_start: usize,
+    host_end: usize,
+}
+
+impl FilterList {
+    /// Compile a filter list, one filter per line.
+    pub fn parse(input: &str) -> Self {
+        Self {
+            filters: input.lines().filter_map(Filter::parse).collect(),
+        }
+    }
+
+    /// Read and compile a filter list file.
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        Ok(Self::parse(&std::fs::read_to_string(path)?))
+    }
+
+    /// Check a request URL, loaded by the page at the document URL, against the filters.
+    /// Exceptions win over blocking filters unless the blocking filter is `$important`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::adblock::{FilterList, FilterMatch};
+    /// use url_parse::core::Parser;
+    /// let list = FilterList::parse("||tracker.net^$third-party");
+    /// let parser = Parser::new(None);
+    /// let request = parser.parse("https://cdn.tracker.net/t.js").unwrap();
+    /// let first_party = parser.parse("https://www.tracker.net/").unwrap();
+    /// let third_party = parser.parse("https://www.example.com/").unwrap();
+    /// assert_eq!(list.check(&request, Some(&first_party)), FilterMatch::NoMatch);
+    /// assert!(matches!(list.check(&request, Some(&third_party)), FilterMatch::Blocked(_)));
+    /// ```
+    pub fn check<'a>(&'a self, request: &Url, document: Option<&Url>) -> FilterMatch<'a> {
+        let prepared = Request::new(request);
+        let lowercase = Request {
+            text: prepared.text.to_ascii_lowercase(),
+            ..prepared
+        };
+        let text_for = |filter: &Filter| match filter.match_case {
+            true => &prepared,
+            false => &lowercase,
+        };
+        let applies = |filter: &&Filter| {
+            filter.applies_to(request, document) && filter.matches(text_for(filter))
+        };
+
+        let blocked_by = self.filters.iter().filter(|f| !f.exception).find(applies);
+        let blocked_by = match blocked_by {
+            Some(v) => v,
+            None => return FilterMatch::NoMatch,
+        };
+        if let Some(important) = self
+            .filters
+            .iter()
+            .filter(|f| !f.exception && f.important)
+            .find(applies)
+        {
+            return FilterMatch::Blocked(important);
+        }
+        match self.filters.iter().filter(|f| f.exception).find(applies) {
+            Some(exception) => FilterMatch::Allowed {
+                exception,
+                blocked_by,
+            },
+            None => FilterMatch::Blocked(blocked_by),
+        }
+    }
+}
+
+impl Filter {
+    /// Compile a single filter. Returns `None` for comments, cosmetic and unsupported filters.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::adblock::Filter;
+    /// let filter = Filter::parse("@@||example.com^$domain=example.org|~shop.example.org").unwrap();
+    /// assert!(filter.exception);
+    /// assert_eq!(filter.include_domains, vec!["example.org"]);
+    /// assert_eq!(filter.exclude_domains, vec!["shop.example.org"]);
+    /// assert!(Filter::parse("! comment").is_none());
+    /// ```
+    pub fn parse(line: &str) -> Option<Filter> {
+        let raw = line.trim();
+        if raw.is_empty()
+            || raw.starts_with('!')
+            || raw.starts_with('[')
+            || raw.contains("##")
+            || raw.contains("#@#")
+            || raw.contains("#?#")
+        {
+            return None;
+        }
+
+        let (exception, rule) = match raw.strip_prefix("@@") {
+            Some(rest) => (true, rest),
+            None => (false, raw),
+        };
+        let (pattern, options) = match rule.rfind('$') {
+            Some(pos) => (&rule[..pos], Some(&rule[pos + 1..])),
+            None => (rule, None),
+        };
+        if pattern.len() > 1 && pattern.starts_with('/') && pattern.ends_with('/') {
+            return None;
+        }
+
+        let mut filter = Filter {
+            raw: raw.to_string(),
+            exception,
+            important: false,
+            match_case: false,
+            third_party: None,
+            include_domains: vec![],
+            exclude_domains: vec![],
+            anchor: Anchor::None,
+            anchor_end: false,
+            tokens: vec![],
+        };
+        if let Some(options) = options {
+            for option in options.split(',') {
+                filter.parse_option(option.trim())?;
+            }
+        }
+        filter.parse_pattern(pattern);
+        Some(filter)
+    }
+
+    fn parse_option(&mut self, option: &str) -> Option<()> {
+        match option {
+            "third-party" | "3p" => self.third_party = Some(true),
+            "~third-party" | "first-party" | "1p" => self.third_party = Some(false),
+            "match-case" => self.match_case = true,
+            "important" => self.important = true,
+            _ => {
+                let domains = option.strip_prefix("domain=")?;
+                for domain in domains.split('|').map(|d| d.to_ascii_lowercase()) {
+                    match domain.strip_prefix('~') {
+                        Some(v) => self.exclude_domains.push(v.to_string()),
+                        None => self.include_domains.push(domain),
+                    }
+                }
+            }
+        }
+        Some(())
+    }
+
+    fn parse_pattern(&mut self, pattern: &str) {
+        let mut pattern = pattern;
+        if let Some(rest) = pattern.strip_prefix("||") {
+            self.anchor = Anchor::Domain;
+            pattern = rest;
+        } else if let Some(rest) = pattern.strip_prefix('|') {
+            self.anchor = Anchor::Start;
+            pattern = rest;
+        }
+        if let Some(rest) = pattern.strip_suffix('|') {
+            self.anchor_end = true;
+            pattern = rest;
+        }
+
+        let pattern = match self.match_case {
+            true => pattern.to_string(),
+            false => pattern.to_ascii_lowercase(),
+        };
+        for c in pattern.bytes() {
+            match (c, self.tokens.last_mut()) {
+                (b'*', Some(Token::Wildcard)) => {}
+                (b'*', _) => self.tokens.push(Token::Wildcard),
+                (b'^', _) => self.tokens.push(Token::Separator),
+                (c, Some(Token::Literal(literal))) => literal.push(c),
+                (c, _) => self.tokens.push(Token::Literal(vec![c])),
+            }
+        }
+    }
+
+    /// Check the options of this filter against the request and document.
+    fn applies_to(&self, request: &Url, document: Option<&Url>) -> bool {
+        let document_host = document
+            .and_then(|d| d.hostname())
+            .map(|h| h.to_ascii_lowercase());
+        if let Some(third_party) = self.third_party {
+            let document = match document {
+                Some(v) => v,
+                None => return false,
+            };
+            let is_third_party = match (site(request), site(document)) {
+                (Some(request), Some(document)) => request != document,
+                _ => false,
+            };
+            if is_third_party != third_party {
+                return false;
+            }
+        }
+        if !self.include_domains.is_empty() {
+            match &document_host {
+                Some(host)
+                    if self
+                        .include_domains
+                        .iter()
+                        .any(|d| is_same_or_subdomain(host, d)) => {}
+                _ => return false,
+            }
+        }
+        if let Some(host) = &document_host {
+            if self
+                .exclude_domains
+                .iter()
+                .any(|d| is_same_or_subdomain(host, d))
+            {
+                return false;
+            }
+        }
+        true
+    }
+
+    fn matches(&self, request: &Request) -> bool {
+        let text = request.text.as_bytes();
+        let starts = match_starts(&self.tokens, text, self.anchor_end);
+        match self.anchor {
+            Anchor::Start => starts[0],
+            Anchor::Domain => {
+                let host = &text[request.host_start..request.host_end];
+                std::iter::once(request.host_start)
+                    .chain(
+                        host.iter()
+                            .enumerate()
+                            .filter(|(_, &c)| c == b'.')
+                            .map(|(pos, _)| request.host_start + pos + 1),
+                    )
+                    .any(|start| starts[start])
+            }
+            Anchor::None => starts.contains(&true),
+        }
+    }
+}
+
+impl Request {
+    /// Prepare `scheme://host[:port]/path?query` for matching. Credentials, default ports and the anchor are left out.
+    fn new(url: &Url) -> Self {
+        let mut text = String::new();
+        if let Some(scheme) = &url.scheme {
+            text += scheme;
+            text += "://";
+        }
+        let host_start = text.len();
+        text += &url.hostname().unwrap_or_default();
+        let host_end = text.len();
+
+        if let Some(port) = url.port {
+            if Some(port) != url.default_port {
+                text += ":";
+                text += &port.to_string();
+            }
+        }
+        text += "/";
+        if let Some(path) = &url.path {
+            text += &path.join("/");
+        }
+        if let Some(query) = &url.query {
+            text += "?";
+            text += query;
+        }
+        Self {
+            text,
+            host_start,
+            host_end,
+        }
+    }
+}
+
+fn is_separator(c: u8) -> bool {
+    !(c.is_ascii_alphanumeric() || matches!(c, b'_' | b'-' | b'.' | b'%'))
+}
+
+/// The lowercase registrable domain of the URL, i.e. `example.co.uk`. `None` if the URL has no host.
+fn site(url: &Url) -> Option<String> {
+    let domain = url.domain.as_deref()?;
+    let site = match &url.top_level_domain {
+        Some(tld) => format!("{domain}.{tld}"),
+        None => domain.to_string(),
+    };
+    Some(site.to_ascii_lowercase())
+}
+
+fn is_same_or_subdomain(host: &str, domain: &str) -> bool {
+    host == domain || host.ends_with(&format!(".{domain}"))
+}
+
+/// Find the positions of the text from which the tokens match. The tokens are matched from the last one backwards, so a
+/// wildcard costs one step per position instead of retrying every position for the rest of the pattern.
+fn match_starts(tokens: &[Token], text: &[u8], anchor_end: bool) -> Vec<bool> {
+    let len = text.len();
+    let mut next: Vec<bool> = (0..=len).map(|pos| !anchor_end || pos == len).collect();
+    for token in tokens.iter().rev() {
+        let mut current = vec![false; len + 1];
+        for pos in (0..=len).rev() {
+            current[pos] = match token {
+                Token::Literal(literal) => {
+                    text[pos..].starts_with(literal) && next[pos + literal.len()]
+                }
+                Token::Separator => match text.get(pos) {
+                    Some(&c) => is_separator(c) && next[pos + 1],
+                    None => next[pos],
+                },
+                Token::Wildcard => next[pos] || (pos < len && current[pos + 1]),
+            };
+        }
+        next = current;
+    }
+    next
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Parser;
+
+    fn is_blocked(list: &str, request: &str, document: Option<&str>) -> bool {
+        let list = FilterList::parse(list);
+        let document = document.map(|d| Parser::new(None).parse(d).unwrap());
+        matches!(
+            list.check(
+                &Parser::new(None).parse(request).unwrap(),
+                document.as_ref()
+            ),
+            FilterMatch::Blocked(_)
+        )
+    }
+
+    #[test]
+    fn test_check_works_when_domain_anchor() {
+        let list = "||ads.example.com^";
+        assert!(is_blocked(list, "https://ads.example.com/x.png", None));
+        assert!(is_blocked(list, "https://eu.ads.example.com/x.png", None));
+        assert!(!is_blocked(list, "https://badads.example.com/x.png", None));
+        assert!(!is_blocked(
+            list,
+            "https://ads.example.community/x.png",
+            None
+        ));
+    }
+
+    #[test]
+    fn test_check_works_when_start_and_end_anchor() {
+        assert!(is_blocked("|https://", "https://www.example.com/", None));
+        assert!(!is_blocked("|http://", "https://www.example.com/", None));
+        assert!(is_blocked(".gif|", "https://www.example.com/a.gif", None));
+        assert!(!is_blocked(
+            ".gif|",
+            "https://www.example.com/a.gif?x=1",
+            None
+        ));
+    }
+
+    #[test]
+    fn test_check_works_when_wildcard_and_separator() {
+        let list = "/banner/*/img^";
+        assert!(is_blocked(
+            list,
+            "https://www.example.com/banner/foo/img?x",
+            None
+        ));
+        assert!(is_blocked(
+            list,
+            "https://www.example.com/banner/foo/img",
+            None
+        ));
+        assert!(!is_blocked(
+            list,
+            "https://www.example.com/banner/foo/imgx",
+            None
+        ));
+    }
+
+    #[test]
+    fn test_check_works_when_many_wildcards() {
+        let list = "a*a*a*a*a*a*a*a*a*a*b";
+        let request = format!("https://example.com/{}", "a".repeat(5000));
+        assert!(!is_blocked(list, &request, None));
+        assert!(is_blocked(list, &(request + "b"), None));
+    }
+
+    #[test]
+    fn test_check_works_when_case_insensitive() {
+        assert!(is_blocked(
+            "/AdServer/*",
+            "https://www.example.com/adserver/x",
+            None
+        ));
+        assert!(!is_blocked(
+            "/AdServer/*$match-case",
+            "https://www.example.com/adserver/x",
+            None
+        ));
+    }
+
+    #[test]
+    fn test_check_works_when_domain_option() {
+        let list = "||cdn.net^$domain=example.com|~shop.example.com";
+        let request = "https://cdn.net/lib.js";
+        assert!(is_blocked(list, request, Some("https://www.example.com/")));
+        assert!(!is_blocked(
+            list,
+            request,
+            Some("https://shop.example.com/")
+        ));
+        assert!(!is_blocked(list, request, Some("https://www.example.org/")));
+        assert!(!is_blocked(list, request, None));
+    }
+
+    #[test]
+    fn test_check_works_when_third_party() {
+        let list = "||tracker.net^$third-party\n||cdn.tracker.net^$~third-party";
+        let request = "https://tracker.net/pixel.gif";
+        assert!(is_blocked(list, request, Some("https://www.example.com/")));
+        assert!(!is_blocked(list, request, Some("https://www.Tracker.net/")));
+        let request = "https://cdn.tracker.net/lib.js";
+        assert!(is_blocked(list, request, Some("https://tracker.net/")));
+    }
+
+    #[test]
+    fn test_check_works_when_hostless_url() {
+        let list = "/passwd$third-party\n/lib.js$~third-party";
+        assert!(!is_blocked(
+            list,
+            "file:///etc/passwd",
+            Some("https://www.example.com/")
+        ));
+        assert!(!is_blocked(
+            list,
+            "https://www.example.com/etc/passwd",
+            Some("file:///tmp/a.html")
+        ));
+        assert!(is_blocked(
+            list,
+            "file:///tmp/lib.js",
+            Some("https://www.example.com/")
+        ));
+        assert!(is_blocked(
+            list,
+            "https://www.example.com/lib.js",
+            Some("/index.html")
+        ));
+    }
+
+    #[test]
+    fn test_check_works_when_exception() {
+        let list = FilterList::parse("||ads.example.com^\n@@||ads.example.com/ok/");
+        let result = list.check(
+            &Parser::new(None)
+                .parse("https://ads.example.com/ok/x.png")
+                .unwrap(),
+            None,
+        );
+        assert_eq!(
+            result,
+            FilterMatch::Allowed {
+                exception: &list.filters[1],
+                blocked_by: &list.filters[0],
+            }
+        );
+    }
+
+    #[test]
+    fn test_check_works_when_important() {
+        let list = FilterList::parse("||ads.example.com^$important\n@@||ads.example.com^");
+        let result = list.check(
+            &Parser::new(None)
+                .parse("https://ads.example.com/x.png")
+                .unwrap(),
+            None,
+        );
+        assert_eq!(result, FilterMatch::Blocked(&list.filters[0]));
+    }
+
+    #[test]
+    fn test_check_works_when_port_not_default() {
+        assert!(is_blocked(
+            "|http://example.com:8080/",
+            "http://example.com:8080/",
+            None
+        ));
+        assert!(is_blocked(
+            "|https://example.com/",
+            "https://example.com:443/",
+            None
+        ));
+    }
+
+    #[test]
+    fn test_parse_skips_when_unsupported() {
+        let list = FilterList::parse(
+            "[Adblock Plus 2.0]\n! Title\nexample.com##.ad\n/banner\\d+/\n||x.com^$script\n||y.com^",
+        );
+        assert_eq!(list.filters.len(), 1);
+        assert_eq!(list.filters[0].raw, "||y.com^");
+    }
+}
@@ -1,7 +1,8 @@
 use crate::core::Parser;
 
 impl Parser {
-    /// Extract the anchor from the url.
+    /// Extract the anchor from the url, everything after the first `#`. An empty anchor (`#` alone) is `Some("")`
+    /// and a missing one is `None`.
     ///
     /// # Example
     /// ```rust
@@ -30,4 +31,18 @@ mod tests {
         let result = Parser::new(None).anchor(input).unwrap();
         assert_eq!(result, "dayone");
     }
+
+    #[test]
+    fn test_anchor_works_when_questionmark_in_anchor() {
+        let input = "https://www.example.co.uk/a#frag?b=1#c";
+        let result = Parser::new(None).anchor(input).unwrap();
+        assert_eq!(result, "frag?b=1#c");
+    }
+
+    #[test]
+    fn test_anchor_works_when_empty() {
+        let parser = Parser::new(None);
+        assert_eq!(parser.anchor("https://www.example.co.uk/a?b#"), Some(""));
+        assert_eq!(parser.anchor("https://www.example.co.uk/a?b"), None);
+    }
 }
@@ -2,7 +2,7 @@ use std::collections::HashMap;
 
 /// Get the default port mappings for well-known ports.
 /// This is a convenience function to create a Parser object (via `Parser::new()`) and pass it defaults.
-pub fn default_port_mappings() -> HashMap<&'static str, (u32, &'static str)> {
+pub fn default_port_mappings() -> HashMap<&'static str, (u16, &'static str)> {
     HashMap::from([
         ("ftp", (21, "File Transfer Protocol")),
         ("http", (80, "Hypertext Transfer Protocol")),
@@ -13,3 +13,26 @@ pub fn default_port_mappings() -> HashMap<&'static str, (u32, &'static str)> {
         ("s3", (443, "Amazon S3 File Transfer Protocol")),
     ])
 }
+
+/// Get the port mappings which are only used to infer a scheme, see `Parser::infer_scheme()`.
+/// They are kept out of the default port mappings, whose keys are recognized as schemes in `name:port` input.
+pub fn inference_port_mappings() -> HashMap<&'static str, (u16, &'static str)> {
+    HashMap::from([
+        ("mysql", (3306, "MySQL Database")),
+        ("postgres", (5432, "PostgreSQL Database")),
+        ("redis", (6379, "Redis Database")),
+    ])
+}
+
+/// Schemes whose urls have no authority and an opaque, rootless path (i.e. `mailto:user@example.com`).
+pub const OPAQUE_SCHEMES: &[&str] = &[
+    "about",
+    "data",
+    "javascript",
+    "magnet",
+    "mailto",
+    "news",
+    "sms",
+    "tel",
+    "urn",
+];
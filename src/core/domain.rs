@@ -22,16 +22,13 @@ impl Parser {
     pub fn domain<'a>(&self, input: &'a str) -> Domain<'a> {
         let input = Utils::substring_after_login(self, input);
         let input = Utils::substring_before_port(self, input);
-        let input = match input.find('/') {
-            Some(pos) => &input[..pos],
-            None => input,
-        };
-        return self
+        let input = &input[..Utils::authority_end(input)];
+        self
             .domain_ipv4(input)
             .or_else(|| self.subdomain_domain_top_level_domain(input))
             .or_else(|| self.subdomain_domain(input))
             .or_else(|| self.domain_alias(input))
-            .unwrap_or_else(Domain::empty);
+            .unwrap_or_else(Domain::empty)
     }
 
     /// Mixes out the subdomain.domain part (i.e.: google.com -> subdomain(None), domain(google), top_level_domain(com))
@@ -42,11 +39,11 @@ impl Parser {
         caps.as_ref()?;
 
         let caps = caps.unwrap();
-        return Some(Domain {
+        Some(Domain {
             subdomain: None,
             domain: Some(caps.get(1).unwrap().as_str()),
             top_level_domain: Some(caps.get(2).unwrap().as_str()),
-        });
+        })
     }
 
     /// Mixes out the subdomain.domain.top_level_domain part (i.e.: www.google.com -> subdomain(www), domain(google), top_level_domain(com))
@@ -57,11 +54,11 @@ impl Parser {
         caps.as_ref()?;
 
         let caps = caps.unwrap();
-        return Some(Domain {
+        Some(Domain {
             subdomain: Some(caps.get(1).unwrap().as_str()),
             domain: Some(caps.get(2).unwrap().as_str()),
             top_level_domain: Some(caps.get(3).unwrap().as_str()),
-        });
+        })
     }
 
     /// Mixes out the ip v4 into a Domain structure.
@@ -69,11 +66,11 @@ impl Parser {
         let re = Regex::new(r"([0-9]+)\.([0-9]+)\.([0-9]+)\.([0-9]+)").unwrap();
         let caps = re.captures(input);
         caps.as_ref()?;
-        return Some(Domain {
+        Some(Domain {
             subdomain: None,
             domain: Some(caps.unwrap().get(0).unwrap().as_str()),
             top_level_domain: None,
-        });
+        })
     }
 
     /// Mixes out single-word alias (i.e.: "localhost") into a Domain structure.
@@ -81,11 +78,11 @@ impl Parser {
         let re = Regex::new(r".+").unwrap();
         let caps = re.captures(input);
         caps.as_ref()?;
-        return Some(Domain {
+        Some(Domain {
             subdomain: None,
             domain: Some(caps.unwrap().get(0).unwrap().as_str()),
             top_level_domain: None,
-        });
+        })
     }
 }
 
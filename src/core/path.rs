@@ -2,7 +2,8 @@ use crate::core::Parser;
 use crate::utils::Utils;
 
 impl Parser {
-    /// Extract the path as a vector from the url.
+    /// Extract the path as a vector from the url. A path of `/` is `[""]` and a trailing slash adds an empty segment.
+    /// Returns `None` if the url has no path.
     ///
     /// # Example
     /// ```rust
@@ -13,18 +14,9 @@ impl Parser {
     /// assert_eq!(result, expected);
     /// ```
     pub fn path<'a>(&self, input: &'a str) -> Option<Vec<&'a str>> {
-        let input = Utils::substring_from_path_begin(self, input).unwrap_or("");
-        let input = Utils::substring_after_port(self, input);
-        let input = match input.chars().next() {
-            Some('/') => &input[1..],
-            _ => input,
-        };
-        let position_questionmark = input.find('?');
-        let path_string = match position_questionmark {
-            Some(v) => &input[..v],
-            None => input,
-        };
-        return Some(path_string.split('/').collect());
+        let input = &Utils::substring_from_path_begin(self, input)?[1..];
+        let end = input.find(['?', '#']).unwrap_or(input.len());
+        Some(input[..end].split('/').collect())
     }
 }
 
@@ -56,6 +48,22 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_path_works_when_trailing_slash() {
+        let parser = Parser::new(None);
+        assert_eq!(parser.path("https://example.com/"), Some(vec![""]));
+        assert_eq!(parser.path("https://example.com/a/"), Some(vec!["a", ""]));
+        assert_eq!(parser.path("https://example.com/a/#b/c"), Some(vec!["a", ""]));
+    }
+
+    #[test]
+    fn test_path_is_none_when_no_path() {
+        let parser = Parser::new(None);
+        assert_eq!(parser.path("https://example.com"), None);
+        assert_eq!(parser.path("https://example.com:8080?next=/a"), None);
+        assert_eq!(parser.path("https://example.com#/a"), None);
+    }
+
     #[test]
     fn test_path_works_when_no_port() {
         let input = "https://www.example.co.uk/blog/article/search?docid=720&hl=en#dayone";
@@ -1,27 +1,38 @@
 use crate::core::Parser;
+use crate::url::QueryPairs;
 
 impl Parser {
-    /// Extract the query from the url.
+    /// Extract the query from the url. The query ends at the first `#`, so a `?` inside the anchor is not a query.
+    /// An empty query (`?` alone) is `Some("")` and a missing one is `None`.
     ///
     /// # Example
     /// ```rust
     /// use url_parse::core::Parser;
+    /// let parser = Parser::new(None);
     /// let input = "https://www.example.co.uk:443/blog/article/search?docid=720&hl=en#dayone";
-    /// let result = Parser::new(None).query(input).unwrap();
-    /// assert_eq!(result, "docid=720&hl=en");
+    /// assert_eq!(parser.query(input), Some("docid=720&hl=en"));
+    /// assert_eq!(parser.query("https://example.com/a#frag?b"), None);
+    /// assert_eq!(parser.query("https://example.com/a?#frag"), Some(""));
     /// ```
     pub fn query<'a>(&self, input: &'a str) -> Option<&'a str> {
-        let position_questionmark = input.find('?');
-        let position_pound = input.find('#');
-        if let Some(v) = position_questionmark {
-            let end = match position_pound {
-                Some(v) => v,
-                None => input.len(),
-            };
-            let after = &input[v + 1..end];
-            return Some(after);
-        }
-        None
+        let before_anchor = &input[..input.find('#').unwrap_or(input.len())];
+        before_anchor
+            .find('?')
+            .map(|position| &before_anchor[position + 1..])
+    }
+
+    /// Extract the decoded query pairs from the url. See `QueryPairs`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// let input = "https://www.example.co.uk/search?docid=720&hl=en&hl=de#dayone";
+    /// let result = Parser::new(None).query_pairs(input);
+    /// assert_eq!(result.get("docid"), Some("720"));
+    /// assert_eq!(result.get_all("hl"), vec!["en", "de"]);
+    /// ```
+    pub fn query_pairs(&self, input: &str) -> QueryPairs {
+        QueryPairs::parse(self.query(input).unwrap_or_default())
     }
 }
 
@@ -42,4 +53,26 @@ mod tests {
         let result = Parser::new(None).query(input).unwrap();
         assert_eq!(result, "docid=720&hl=en");
     }
+
+    #[test]
+    fn test_query_is_none_when_questionmark_in_anchor() {
+        let input = "https://www.example.co.uk/a#frag?b=1";
+        let result = Parser::new(None).query(input);
+        assert_eq!(result, None);
+    }
+
+    #[test]
+    fn test_query_works_when_empty() {
+        let parser = Parser::new(None);
+        assert_eq!(parser.query("https://www.example.co.uk/a?"), Some(""));
+        assert_eq!(parser.query("https://www.example.co.uk/a?#"), Some(""));
+        assert_eq!(parser.query("https://www.example.co.uk/a"), None);
+    }
+
+    #[test]
+    fn test_query_works_when_anchor_contains_questionmark() {
+        let input = "https://www.example.co.uk/a?x=1#frag?b=2";
+        let result = Parser::new(None).query(input).unwrap();
+        assert_eq!(result, "x=1");
+    }
 }
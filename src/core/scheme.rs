@@ -1,3 +1,5 @@
+use crate::core::defaults::{inference_port_mappings, OPAQUE_SCHEMES};
+use crate::core::scheme_inference::SchemeInference;
 use crate::core::scheme_separator::SchemeSeparator;
 use crate::core::Parser;
 impl Parser {
@@ -22,19 +24,59 @@ impl Parser {
     /// assert_eq!(scheme.unwrap(), ("https",  SchemeSeparator::Colon));
     /// ```
     pub fn scheme<'a>(&self, input: &'a str) -> Option<(&'a str, SchemeSeparator)> {
-        let split: Vec<&str> = input.split("://").collect();
-
-        if split.len() == 2 {
-            return Some((split[0], SchemeSeparator::ColonSlashSlash));
+        if let Some((scheme, _)) = input.split_once("://") {
+            let mut chars = scheme.chars();
+            let valid = chars.next().is_some_and(|c| c.is_ascii_alphabetic())
+                && chars.all(|c| c.is_ascii_alphanumeric() || "+-.".contains(c));
+            if valid {
+                return Some((scheme, SchemeSeparator::ColonSlashSlash));
+            }
         };
 
         let split: Vec<&str> = input.split(':').collect();
+        if split.len() > 1 && OPAQUE_SCHEMES.contains(&split[0]) {
+            return Some((split[0], SchemeSeparator::Colon));
+        }
         let scheme = self
             .port_mappings
             .keys()
             .find(|&protocol| &split[0] == protocol)?;
         Some((scheme, SchemeSeparator::Colon))
     }
+
+    /// Propose a scheme for a url written without one, by looking up its port in the known port mappings and in
+    /// well-known database ports (`mysql`, `postgres`, `redis`). Returns `None` if the url has a scheme, has no port or the port belongs to no known scheme.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// use url_parse::core::scheme_inference::SchemeInference;
+    /// let parser = Parser::new(None);
+    /// assert_eq!(parser.infer_scheme("db.internal:5432"), Some(SchemeInference::Unique("postgres")));
+    /// assert_eq!(
+    ///     parser.infer_scheme("host:22"),
+    ///     Some(SchemeInference::Ambiguous(vec!["scp", "sftp", "ssh"]))
+    /// );
+    /// ```
+    pub fn infer_scheme(&self, input: &str) -> Option<SchemeInference> {
+        if self.scheme(input).is_some() {
+            return None;
+        }
+        let port = self.port(input)?;
+        let mut mappings = inference_port_mappings();
+        mappings.extend(self.port_mappings.iter().map(|(k, v)| (*k, *v)));
+        let mut candidates: Vec<&'static str> = mappings
+            .iter()
+            .filter(|(_, (p, _))| *p == port)
+            .map(|(scheme, _)| *scheme)
+            .collect();
+        candidates.sort_unstable();
+        match candidates.len() {
+            0 => None,
+            1 => Some(SchemeInference::Unique(candidates[0])),
+            _ => Some(SchemeInference::Ambiguous(candidates)),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -72,10 +114,70 @@ mod tests {
         assert!(scheme.is_none());
     }
 
+    #[test]
+    fn test_scheme_works_when_opaque() {
+        let input = "mailto:user@example.com";
+        let scheme = Parser::new(None).scheme(input);
+        assert_eq!(scheme.unwrap(), ("mailto", SchemeSeparator::Colon));
+    }
+
+    #[test]
+    fn test_infer_scheme_works_when_unique() {
+        let parser = Parser::new(None);
+        assert_eq!(
+            parser.infer_scheme("user@db.internal:3306/app"),
+            Some(SchemeInference::Unique("mysql"))
+        );
+        assert_eq!(
+            parser.infer_scheme("cache:6379"),
+            Some(SchemeInference::Unique("redis"))
+        );
+    }
+
+    #[test]
+    fn test_scheme_is_none_when_inference_only_name_and_port() {
+        let parser = Parser::new(None);
+        for input in ["postgres:5432", "redis:6379/0", "mysql:3306"] {
+            assert_eq!(parser.scheme(input), None, "{input}");
+            let result = parser.parse(input).unwrap();
+            assert_eq!(result.scheme, None);
+            assert_eq!(result.default_port, None);
+        }
+        let result = parser.parse("redis:6379/0").unwrap();
+        assert_eq!(result.port, Some(6379));
+        assert_eq!(result.path, Some(vec!["0".to_string()]));
+    }
+
+    #[test]
+    fn test_infer_scheme_works_when_ambiguous() {
+        let result = Parser::new(None).infer_scheme("example.com:443");
+        assert_eq!(
+            result,
+            Some(SchemeInference::Ambiguous(vec!["https", "s3"]))
+        );
+    }
+
+    #[test]
+    fn test_infer_scheme_is_none_when_not_applicable() {
+        let parser = Parser::new(None);
+        assert_eq!(parser.infer_scheme("https://example.com:5432"), None);
+        assert_eq!(parser.infer_scheme("example.com"), None);
+        assert_eq!(parser.infer_scheme("example.com:1234"), None);
+    }
+
     #[test]
     fn test_scheme_works_when_no_double_slashes() {
         let input = "https:www.example.co.uk/blog/article/search?docid=720&hl=en#dayone";
         let scheme = Parser::new(None).scheme(input);
         assert_eq!(scheme.unwrap().0, "https");
     }
+
+    #[test]
+    fn test_parse_scheme_works_when_url_in_query() {
+        let input = "https://www.google.com/url?q=https://example.com:8080/a";
+        let result = Parser::new(None).scheme(input);
+        assert_eq!(result, Some(("https", SchemeSeparator::ColonSlashSlash)));
+        let input = "www.google.com/url?q=https://example.com";
+        assert_eq!(Parser::new(None).scheme(input), None);
+    }
 }
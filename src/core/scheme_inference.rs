@@ -0,0 +1,8 @@
+/// The outcome of inferring a scheme from a well-known port.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SchemeInference {
+    /// Exactly one known scheme uses the port.
+    Unique(&'static str),
+    /// Several known schemes share the port, in alphabetical order.
+    Ambiguous(Vec<&'static str>),
+}
@@ -0,0 +1,238 @@
+use crate::error::ParseError;
+
+/// The bytes which are percent-encoded in a given URL component.
+///
+/// Control characters and non-ASCII bytes are encoded by every set; space is encoded by every set but `C0Control`.
+/// The sets follow the WHATWG URL standard, except `PathSegment` which keeps only the RFC 3986 `pchar` characters.
+///
+/// # Example
+/// ```rust
+/// use url_parse::encoding::{encode, EncodeSet};
+/// assert_eq!(encode("a b/c?d", EncodeSet::Path), "a%20b/c%3Fd");
+/// assert_eq!(encode("a b/c?d", EncodeSet::PathSegment), "a%20b%2Fc%3Fd");
+/// assert_eq!(encode("a b&c=d", EncodeSet::FormUrlencoded), "a+b%26c%3Dd");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodeSet {
+    /// Control characters and non-ASCII bytes only.
+    C0Control,
+    /// The fragment after `#`.
+    Fragment,
+    /// The query of a URL with a non-special scheme.
+    Query,
+    /// The query of a URL with a special scheme (`http`, `https`, `ftp`, ...), which also encodes `'`.
+    SpecialQuery,
+    /// A path, leaving `/` unencoded.
+    Path,
+    /// A single path segment, encoding everything but RFC 3986 unreserved characters, sub-delimiters, `:` and `@`.
+    PathSegment,
+    /// The username or password.
+    Userinfo,
+    /// Any single component, like JavaScript's `encodeURIComponent`.
+    Component,
+    /// `application/x-www-form-urlencoded` keys and values. Spaces become `+`.
+    FormUrlencoded,
+}
+
+impl EncodeSet {
+    /// Check whether a byte is percent-encoded by this set.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::encoding::EncodeSet;
+    /// assert!(EncodeSet::Userinfo.contains(b'@'));
+    /// assert!(!EncodeSet::Path.contains(b'@'));
+    /// ```
+    pub fn contains(&self, byte: u8) -> bool {
+        if !(0x20..=0x7e).contains(&byte) {
+            return true;
+        }
+        match self {
+            EncodeSet::C0Control => false,
+            EncodeSet::Fragment => matches!(byte, b' ' | b'"' | b'<' | b'>' | b'`'),
+            EncodeSet::Query => matches!(byte, b' ' | b'"' | b'#' | b'<' | b'>'),
+            EncodeSet::SpecialQuery => EncodeSet::Query.contains(byte) || byte == b'\'',
+            EncodeSet::Path => {
+                EncodeSet::Query.contains(byte) || matches!(byte, b'?' | b'^' | b'`' | b'{' | b'}')
+            }
+            EncodeSet::PathSegment => !matches!(byte,
+                b'a'..=b'z' | b'A'..=b'Z' | b'0'..=b'9'
+                | b'-' | b'.' | b'_' | b'~'
+                | b'!' | b'$' | b'&' | b'\'' | b'(' | b')' | b'*' | b'+' | b',' | b';' | b'='
+                | b':' | b'@'),
+            EncodeSet::Userinfo => {
+                EncodeSet::Path.contains(byte)
+                    || matches!(
+                        byte,
+                        b'/' | b':' | b';' | b'=' | b'@' | b'[' | b'\\' | b']' | b'|'
+                    )
+            }
+            EncodeSet::Component => {
+                EncodeSet::Userinfo.contains(byte)
+                    || matches!(byte, b'$' | b'%' | b'&' | b'+' | b',')
+            }
+            EncodeSet::FormUrlencoded => {
+                EncodeSet::Component.contains(byte)
+                    || matches!(byte, b'!' | b'\'' | b'(' | b')' | b'~')
+            }
+        }
+    }
+}
+
+/// Percent-encode the UTF-8 bytes of the input which belong to the encode set.
+///
+/// # Example
+/// ```rust
+/// use url_parse::encoding::{encode, EncodeSet};
+/// assert_eq!(encode("p@ss wörd", EncodeSet::Userinfo), "p%40ss%20w%C3%B6rd");
+/// ```
+pub fn encode(input: &str, set: EncodeSet) -> String {
+    let mut result = String::with_capacity(input.len());
+    for byte in input.bytes() {
+        match byte {
+            b' ' if set == EncodeSet::FormUrlencoded => result.push('+'),
+            b if set.contains(b) => result.push_str(&format!("%{b:02X}")),
+            b => result.push(b as char),
+        }
+    }
+    result
+}
+
+/// Decode percent-encoded octets to bytes. Invalid escapes are kept as they are.
+///
+/// # Example
+/// ```rust
+/// use url_parse::encoding::decode;
+/// assert_eq!(decode("%F0%9F%A6%80%zz"), vec![0xF0, 0x9F, 0xA6, 0x80, b'%', b'z', b'z']);
+/// ```
+pub fn decode(input: &str) -> Vec<u8> {
+    let bytes = input.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut pos = 0;
+    while pos < bytes.len() {
+        let escaped = match bytes[pos] {
+            b'%' => input
+                .get(pos + 1..pos + 3)
+                .filter(|hex| hex.bytes().all(|b| b.is_ascii_hexdigit()))
+                .and_then(|hex| u8::from_str_radix(hex, 16).ok()),
+            _ => None,
+        };
+        match escaped {
+            Some(v) => {
+                result.push(v);
+                pos += 3;
+            }
+            None => {
+                result.push(bytes[pos]);
+                pos += 1;
+            }
+        }
+    }
+    result
+}
+
+/// Decode percent-encoded octets to a string, replacing invalid UTF-8.
+///
+/// # Example
+/// ```rust
+/// use url_parse::encoding::decode_utf8_lossy;
+/// assert_eq!(decode_utf8_lossy("caf%C3%A9%FF"), "café\u{FFFD}");
+/// ```
+pub fn decode_utf8_lossy(input: &str) -> String {
+    String::from_utf8_lossy(&decode(input)).into_owned()
+}
+
+/// Decode percent-encoded octets to a string, failing on invalid UTF-8.
+///
+/// # Example
+/// ```rust
+/// use url_parse::encoding::decode_utf8;
+/// assert_eq!(decode_utf8("caf%C3%A9").unwrap(), "café");
+/// assert!(decode_utf8("caf%C3").is_err());
+/// ```
+pub fn decode_utf8(input: &str) -> Result<String, ParseError> {
+    String::from_utf8(decode(input)).map_err(|_| ParseError {})
+}
+
+/// Decode an `application/x-www-form-urlencoded` key or value: `+` becomes a space and invalid UTF-8 is replaced.
+///
+/// # Example
+/// ```rust
+/// use url_parse::encoding::decode_form;
+/// assert_eq!(decode_form("a+b%2Bc"), "a b+c");
+/// ```
+pub fn decode_form(input: &str) -> String {
+    decode_utf8_lossy(&input.replace('+', " "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_encode_works_when_sets_differ() {
+        let input = "a b\"#<>?`{}/:;=@[]|$%&+,!'()~";
+        assert_eq!(
+            encode(input, EncodeSet::Fragment),
+            "a%20b%22#%3C%3E?%60{}/:;=@[]|$%&+,!'()~"
+        );
+        assert_eq!(
+            encode(input, EncodeSet::Query),
+            "a%20b%22%23%3C%3E?`{}/:;=@[]|$%&+,!'()~"
+        );
+        assert_eq!(
+            encode(input, EncodeSet::SpecialQuery),
+            "a%20b%22%23%3C%3E?`{}/:;=@[]|$%&+,!%27()~"
+        );
+        assert_eq!(
+            encode(input, EncodeSet::Path),
+            "a%20b%22%23%3C%3E%3F%60%7B%7D/:;=@[]|$%&+,!'()~"
+        );
+        assert_eq!(
+            encode(input, EncodeSet::Userinfo),
+            "a%20b%22%23%3C%3E%3F%60%7B%7D%2F%3A%3B%3D%40%5B%5D%7C$%&+,!'()~"
+        );
+        assert_eq!(
+            encode(input, EncodeSet::Component),
+            "a%20b%22%23%3C%3E%3F%60%7B%7D%2F%3A%3B%3D%40%5B%5D%7C%24%25%26%2B%2C!'()~"
+        );
+        assert_eq!(
+            encode(input, EncodeSet::FormUrlencoded),
+            "a+b%22%23%3C%3E%3F%60%7B%7D%2F%3A%3B%3D%40%5B%5D%7C%24%25%26%2B%2C%21%27%28%29%7E"
+        );
+        assert_eq!(
+            encode(input, EncodeSet::PathSegment),
+            "a%20b%22%23%3C%3E%3F%60%7B%7D%2F:;=@%5B%5D%7C$%25&+,!'()~"
+        );
+    }
+
+    #[test]
+    fn test_encode_works_when_control_and_non_ascii() {
+        assert_eq!(encode("\t\u{7f}é", EncodeSet::C0Control), "%09%7F%C3%A9");
+    }
+
+    #[test]
+    fn test_decode_works_when_roundtrip() {
+        let input = "ünï cødé/?#%+";
+        for set in [
+            EncodeSet::PathSegment,
+            EncodeSet::Component,
+            EncodeSet::FormUrlencoded,
+        ] {
+            let encoded = encode(input, set);
+            let decoded = match set {
+                EncodeSet::FormUrlencoded => decode_form(&encoded),
+                _ => decode_utf8(&encoded).unwrap(),
+            };
+            assert_eq!(decoded, input);
+        }
+    }
+
+    #[test]
+    fn test_decode_works_when_invalid_escape() {
+        assert_eq!(decode_utf8_lossy("100%"), "100%");
+        assert_eq!(decode_utf8_lossy("%4"), "%4");
+        assert_eq!(decode_utf8_lossy("%%41"), "%A");
+        assert_eq!(decode_utf8_lossy("m%C3%BCnchen%"), "münchen%");
+    }
+}
@@ -11,9 +11,72 @@ impl Display for ParseError {
 
 impl std::error::Error for ParseError {}
 
+/// Errors raised while loading a `Policy` from a file or string.
+#[derive(Debug)]
+pub enum PolicyError {
+    Io(std::io::Error),
+    Format(String),
+    UnsupportedFormat(String),
+}
+
+impl Display for PolicyError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            PolicyError::Io(e) => write!(f, "PolicyError: {e}"),
+            PolicyError::Format(e) => write!(f, "PolicyError: {e}"),
+            PolicyError::UnsupportedFormat(v) => {
+                write!(f, "PolicyError: unsupported format '{v}'")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PolicyError {}
+
+impl From<std::io::Error> for PolicyError {
+    fn from(e: std::io::Error) -> Self {
+        PolicyError::Io(e)
+    }
+}
+
+/// Errors raised while mapping a query to or from a struct, naming the offending key when known.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct QueryError {
+    pub key: Option<String>,
+    pub message: String,
+}
+
+impl Display for QueryError {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match &self.key {
+            Some(key) => write!(f, "QueryError: key '{key}': {}", self.message),
+            None => write!(f, "QueryError: {}", self.message),
+        }
+    }
+}
+
+impl std::error::Error for QueryError {}
+
 #[test]
 fn test_err_display() {
     let e = ParseError {};
     let x = format!("{e}");
     assert_eq!(x, "ParseError")
 }
+
+#[test]
+fn test_policy_err_display() {
+    let e = PolicyError::UnsupportedFormat("yaml".to_string());
+    let x = format!("{e}");
+    assert_eq!(x, "PolicyError: unsupported format 'yaml'")
+}
+
+#[test]
+fn test_query_err_display() {
+    let e = QueryError {
+        key: Some("page".to_string()),
+        message: "invalid digit found in string".to_string(),
+    };
+    let x = format!("{e}");
+    assert_eq!(x, "QueryError: key 'page': invalid digit found in string")
+}
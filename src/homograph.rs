@@ -0,0 +1,546 @@
+use crate::encoding::decode_utf8_lossy;
+use crate::url::Url;
+use core::fmt::Display;
+
+/// Unicode scripts relevant for spotting look-alike host names.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Script {
+    /// Digits, punctuation and symbols shared by all scripts.
+    Common,
+    /// Combining marks and joiners which take the script of the preceding character.
+    Inherited,
+    Latin,
+    Greek,
+    Cyrillic,
+    Armenian,
+    Hebrew,
+    Arabic,
+    Devanagari,
+    Thai,
+    Georgian,
+    Hangul,
+    Hiragana,
+    Katakana,
+    Han,
+    Unknown,
+}
+
+/// A single suspicious property of a URL.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Finding {
+    /// A host label mixes characters from several scripts, i.e. Cyrillic `раура` with Latin `l`.
+    MixedScript { label: String, scripts: Vec<Script> },
+    /// A host label written entirely in a non-Latin script looks like a Latin label.
+    WholeScriptConfusable {
+        label: String,
+        script: Script,
+        skeleton: String,
+    },
+    /// The host looks like, but is not, one of the protected hosts.
+    ConfusableWith { host: String, target: String },
+    /// An invisible or bidirectional control character was found in a component of the URL.
+    InvisibleCharacter {
+        component: &'static str,
+        character: char,
+    },
+}
+
+/// Overall risk level of a `RiskReport`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Risk {
+    None,
+    Suspicious,
+    High,
+}
+
+/// The result of analyzing a URL for homograph attacks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct RiskReport {
+    /// The host as displayed to users, with punycode (`xn--`) labels decoded.
+    pub host: Option<String>,
+    pub findings: Vec<Finding>,
+}
+
+/// Analyze a URL for mixed-script and whole-script confusable host labels and invisible characters, also when they are
+/// percent-encoded.
+///
+/// # Example
+/// ```rust
+/// use url_parse::core::Parser;
+/// use url_parse::homograph::{analyze, Risk};
+/// let url = Parser::new(None).parse("https://раураl.com/login").unwrap();
+/// let report = analyze(&url);
+/// assert_eq!(report.risk(), Risk::High);
+///
+/// let url = Parser::new(None).parse("https://paypal.com/login").unwrap();
+/// assert_eq!(analyze(&url).risk(), Risk::None);
+/// ```
+pub fn analyze(url: &Url) -> RiskReport {
+    analyze_against(url, &[])
+}
+
+/// Analyze a URL like `analyze()` and additionally flag hosts which are confusable with one of the protected hosts.
+///
+/// # Example
+/// ```rust
+/// use url_parse::core::Parser;
+/// use url_parse::homograph::{analyze_against, Finding};
+/// let url = Parser::new(None).parse("https://rnicrosoft.com").unwrap();
+/// let report = analyze_against(&url, &["microsoft.com"]);
+/// assert_eq!(
+///     report.findings,
+///     vec![Finding::ConfusableWith {
+///         host: "rnicrosoft.com".to_string(),
+///         target: "microsoft.com".to_string()
+///     }]
+/// );
+/// ```
+pub fn analyze_against(url: &Url, protected_hosts: &[&str]) -> RiskReport {
+    let host = url.hostname().map(|h| {
+        h.split('.')
+            .map(|label| decode_label(label).unwrap_or_else(|| label.to_string()))
+            .collect::<Vec<String>>()
+            .join(".")
+    });
+
+    let mut findings = vec![];
+    if let Some(host) = &host {
+        for label in host.split('.') {
+            findings.extend(analyze_label(label));
+        }
+        let host_skeleton = skeleton(host);
+        for target in protected_hosts {
+            if !host.eq_ignore_ascii_case(target) && host_skeleton == skeleton(target) {
+                findings.push(Finding::ConfusableWith {
+                    host: host.to_owned(),
+                    target: target.to_string(),
+                });
+            }
+        }
+    }
+
+    let (user, pass) = &url.user_pass;
+    let components = [
+        ("scheme", url.scheme.clone()),
+        ("username", user.clone()),
+        ("password", pass.clone()),
+        ("host", url.hostname()),
+        ("path", url.path.as_ref().map(|p| p.join("/"))),
+        ("query", url.query.clone()),
+        ("anchor", url.anchor.clone()),
+    ];
+    for (component, value) in components {
+        for character in decode_utf8_lossy(&value.unwrap_or_default()).chars() {
+            if is_invisible(character) {
+                findings.push(Finding::InvisibleCharacter {
+                    component,
+                    character,
+                });
+            }
+        }
+    }
+
+    RiskReport { host, findings }
+}
+
+/// Compute the skeleton of a string: every character is replaced by its Latin prototype according to the
+/// UTS #39 confusables data, so that `skeleton(a) == skeleton(b)` when `a` and `b` look alike.
+///
+/// The input is lowercased first and the mapping table is the subset of `confusables.txt` which maps
+/// Cyrillic, Greek, Armenian, fullwidth and ASCII look-alikes onto lowercase Latin letters and digits.
+///
+/// # Example
+/// ```rust
+/// use url_parse::homograph::skeleton;
+/// assert_eq!(skeleton("аррӏе"), "apple");
+/// assert_eq!(skeleton("paypa1"), skeleton("paypal"));
+/// ```
+pub fn skeleton(input: &str) -> String {
+    let mut result = String::new();
+    for c in input.chars().flat_map(char::to_lowercase) {
+        match prototype(c) {
+            Some(v) => result += v,
+            None => result.push(c),
+        }
+    }
+    result
+}
+
+impl Script {
+    /// Determine the script of a character.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::homograph::Script;
+    /// assert_eq!(Script::of('a'), Script::Latin);
+    /// assert_eq!(Script::of('а'), Script::Cyrillic);
+    /// assert_eq!(Script::of('-'), Script::Common);
+    /// ```
+    pub fn of(c: char) -> Script {
+        match c as u32 {
+            0x41..=0x5A | 0x61..=0x7A | 0xAA | 0xBA => Script::Latin,
+            0x00..=0x7F | 0xD7 | 0xF7 => Script::Common,
+            0xC0..=0x24F | 0x250..=0x2AF | 0x1D00..=0x1D7F | 0x1E00..=0x1EFF => Script::Latin,
+            0x2C60..=0x2C7F | 0xA720..=0xA7FF | 0xFF21..=0xFF3A | 0xFF41..=0xFF5A => Script::Latin,
+            0x300..=0x36F | 0x200C..=0x200D | 0xFE00..=0xFE0F => Script::Inherited,
+            0x370..=0x3FF | 0x1F00..=0x1FFF => Script::Greek,
+            0x400..=0x52F | 0x1C80..=0x1C8F | 0x2DE0..=0x2DFF | 0xA640..=0xA69F => Script::Cyrillic,
+            0x530..=0x58F | 0xFB13..=0xFB17 => Script::Armenian,
+            0x590..=0x5FF | 0xFB1D..=0xFB4F => Script::Hebrew,
+            0x600..=0x6FF | 0x750..=0x77F | 0xFB50..=0xFDFF | 0xFE70..=0xFEFE => Script::Arabic,
+            0x900..=0x97F => Script::Devanagari,
+            0xE00..=0xE7F => Script::Thai,
+            0x10A0..=0x10FF | 0x2D00..=0x2D2F => Script::Georgian,
+            0x1100..=0x11FF | 0x3130..=0x318F | 0xAC00..=0xD7AF => Script::Hangul,
+            0x3040..=0x309F => Script::Hiragana,
+            0x30A0..=0x30FF | 0x31F0..=0x31FF | 0xFF66..=0xFF9F => Script::Katakana,
+            0x3400..=0x4DBF | 0x4E00..=0x9FFF | 0xF900..=0xFAFF | 0x20000..=0x2FA1F => Script::Han,
+            0x80..=0xBF | 0x2000..=0x2BFF | 0x3000..=0x303F | 0xFF00..=0xFF20 => Script::Common,
+            _ => Script::Unknown,
+        }
+    }
+}
+
+impl RiskReport {
+    /// Summarize the findings: mixed scripts are suspicious, or high risk when Latin is one of them.
+    /// All other findings are high risk.
+    pub fn risk(&self) -> Risk {
+        self.findings
+            .iter()
+            .map(|finding| match finding {
+                Finding::MixedScript { scripts, .. } if scripts.contains(&Script::Latin) => {
+                    Risk::High
+                }
+                Finding::MixedScript { .. } => Risk::Suspicious,
+                _ => Risk::High,
+            })
+            .max()
+            .unwrap_or(Risk::None)
+    }
+}
+
+impl Display for Finding {
+    fn fmt(&self, f: &mut core::fmt::Formatter) -> core::fmt::Result {
+        match self {
+            Finding::MixedScript { label, scripts } => {
+                write!(f, "'{label}' mixes the scripts {scripts:?}")
+            }
+            Finding::WholeScriptConfusable {
+                label,
+                script,
+                skeleton,
+            } => write!(
+                f,
+                "'{label}' is written in {script:?} but looks like '{skeleton}'"
+            ),
+            Finding::ConfusableWith { host, target } => {
+                write!(f, "'{host}' looks like '{target}'")
+            }
+            Finding::InvisibleCharacter {
+                component,
+                character,
+            } => write!(
+                f,
+                "{component} contains the invisible character U+{:04X}",
+                *character as u32
+            ),
+        }
+    }
+}
+
+/// Script combinations which are common in legitimate labels (UTS #39 "highly restrictive").
+const ALLOWED_SCRIPT_SETS: &[&[Script]] = &[
+    &[
+        Script::Latin,
+        Script::Han,
+        Script::Hiragana,
+        Script::Katakana,
+    ],
+    &[Script::Latin, Script::Han, Script::Hangul],
+];
+
+fn analyze_label(label: &str) -> Vec<Finding> {
+    let mut scripts: Vec<Script> = label
+        .chars()
+        .map(Script::of)
+        .filter(|s| *s != Script::Common && *s != Script::Inherited)
+        .collect();
+    scripts.sort();
+    scripts.dedup();
+
+    if scripts.len() > 1 {
+        let allowed = ALLOWED_SCRIPT_SETS
+            .iter()
+            .any(|set| scripts.iter().all(|s| set.contains(s)));
+        if !allowed {
+            return vec![Finding::MixedScript {
+                label: label.to_string(),
+                scripts,
+            }];
+        }
+    }
+
+    if let [script] = scripts[..] {
+        let skeleton = skeleton(label);
+        if script != Script::Latin
+            && skeleton
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-')
+        {
+            return vec![Finding::WholeScriptConfusable {
+                label: label.to_string(),
+                script,
+                skeleton,
+            }];
+        }
+    }
+    vec![]
+}
+
+fn is_invisible(c: char) -> bool {
+    matches!(
+        c as u32,
+        0xAD | 0x34F
+            | 0x61C
+            | 0x115F..=0x1160
+            | 0x17B4..=0x17B5
+            | 0x180B..=0x180E
+            | 0x200B..=0x200F
+            | 0x202A..=0x202E
+            | 0x2060..=0x2064
+            | 0x2066..=0x206F
+            | 0x3164
+            | 0xFE00..=0xFE0F
+            | 0xFEFF
+            | 0xFFA0
+            | 0xE0000..=0xE007F
+    )
+}
+
+/// Latin prototype of a confusable character, taken from the Unicode `confusables.txt` data.
+fn prototype(c: char) -> Option<&'static str> {
+    if let '\u{FF01}'..='\u{FF5E}' = c {
+        let pos = (c as u32 - 0xFF01) as usize;
+        let ascii = &PRINTABLE_ASCII[pos..pos + 1];
+        return prototype(ascii.chars().next().unwrap()).or(Some(ascii));
+    }
+    let v = match c {
+        '0' | 'о' | 'ο' | 'օ' | 'σ' | 'ᴏ' => "o",
+        '1' | 'ӏ' | 'ℓ' | '|' | 'ǀ' | 'ⅼ' => "l",
+        'і' | 'ι' | 'ı' | 'ɩ' | 'ⅰ' => "i",
+        'а' | 'α' | 'ɑ' => "a",
+        'ь' | 'Ꮟ' => "b",
+        'с' | 'ϲ' | 'ᴄ' | 'ⅽ' => "c",
+        'ԁ' | 'ⅾ' => "d",
+        'е' | 'ҽ' | 'ℯ' => "e",
+        'ց' | 'ɡ' | 'ℊ' => "g",
+        'һ' | 'հ' => "h",
+        'ј' | 'ϳ' => "j",
+        'κ' | 'ĸ' => "k",
+        'm' | 'ⅿ' => "rn",
+        'ո' | 'ռ' => "n",
+        'р' | 'ρ' | 'ϱ' => "p",
+        'ԛ' | 'զ' => "q",
+        'г' | 'ᴦ' => "r",
+        'ѕ' | 'ꜱ' => "s",
+        'τ' => "t",
+        'υ' | 'ս' | 'ʋ' => "u",
+        'ν' | 'ѵ' | 'ⅴ' => "v",
+        'ԝ' | 'ѡ' | 'ɯ' => "w",
+        'х' | 'χ' | 'ⅹ' => "x",
+        'у' | 'γ' | 'ү' => "y",
+        'ᴢ' => "z",
+        'з' | 'ʒ' => "3",
+        _ => return None,
+    };
+    Some(v)
+}
+
+const PRINTABLE_ASCII: &str =
+    "!\"#$%&'()*+,-./0123456789:;<=>?@ABCDEFGHIJKLMNOPQRSTUVWXYZ[\\]^_`abcdefghijklmnopqrstuvwxyz{|}~";
+
+/// Decode an `xn--` label (RFC 3492). Returns `None` for other labels and invalid input.
+fn decode_label(label: &str) -> Option<String> {
+    const BASE: u32 = 36;
+    const T_MIN: u32 = 1;
+    const T_MAX: u32 = 26;
+
+    let encoded = label
+        .get(..4)?
+        .eq_ignore_ascii_case("xn--")
+        .then(|| &label[4..])?;
+    let (basic, extended) = match encoded.rfind('-') {
+        Some(pos) => (&encoded[..pos], &encoded[pos + 1..]),
+        None => ("", encoded),
+    };
+    if !basic.is_ascii() {
+        return None;
+    }
+    let mut output: Vec<char> = basic.chars().collect();
+    let (mut n, mut i, mut bias) = (128u32, 0u32, 72u32);
+
+    let mut digits = extended.bytes().peekable();
+    while digits.peek().is_some() {
+        let old_i = i;
+        let mut w = 1u32;
+        let mut k = BASE;
+        loop {
+            let digit = match digits.next()? {
+                c @ b'a'..=b'z' => c - b'a',
+                c @ b'A'..=b'Z' => c - b'A',
+                c @ b'0'..=b'9' => c - b'0' + 26,
+                _ => return None,
+            } as u32;
+            i = i.checked_add(digit.checked_mul(w)?)?;
+            let t = if k <= bias {
+                T_MIN
+            } else if k >= bias + T_MAX {
+                T_MAX
+            } else {
+                k - bias
+            };
+            if digit < t {
+                break;
+            }
+            w = w.checked_mul(BASE - t)?;
+            k += BASE;
+        }
+        let length = output.len() as u32 + 1;
+        bias = adapt(i - old_i, length, old_i == 0);
+        n = n.checked_add(i / length)?;
+        i %= length;
+        output.insert(i as usize, char::from_u32(n)?);
+        i += 1;
+    }
+    Some(output.into_iter().collect())
+}
+
+fn adapt(delta: u32, points: u32, first: bool) -> u32 {
+    let mut delta = if first { delta / 700 } else { delta / 2 };
+    delta += delta / points;
+    let mut k = 0;
+    while delta > ((36 - 1) * 26) / 2 {
+        delta /= 36 - 1;
+        k += 36;
+    }
+    k + (36 * delta) / (delta + 38)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Parser;
+
+    #[test]
+    fn test_analyze_works_when_mixed_script() {
+        let report = analyze(&Parser::new(None).parse("https://раураl.com/").unwrap());
+        assert_eq!(
+            report.findings,
+            vec![Finding::MixedScript {
+                label: "раураl".to_string(),
+                scripts: vec![Script::Latin, Script::Cyrillic],
+            }]
+        );
+        assert_eq!(report.risk(), Risk::High);
+    }
+
+    #[test]
+    fn test_analyze_works_when_whole_script_confusable_punycode() {
+        let report = analyze(
+            &Parser::new(None)
+                .parse("https://xn--80ak6aa92e.com/")
+                .unwrap(),
+        );
+        assert_eq!(report.host, Some("аррӏе.com".to_string()));
+        assert_eq!(
+            report.findings,
+            vec![Finding::WholeScriptConfusable {
+                label: "аррӏе".to_string(),
+                script: Script::Cyrillic,
+                skeleton: "apple".to_string(),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_analyze_works_when_legitimate_non_latin() {
+        let report = analyze(&Parser::new(None).parse("https://пример.рф/").unwrap());
+        assert!(report.findings.is_empty());
+        let report = analyze(&Parser::new(None).parse("https://東京tower.jp/").unwrap());
+        assert!(report.findings.is_empty());
+    }
+
+    #[test]
+    fn test_analyze_works_when_invisible_character() {
+        let report = analyze(
+            &Parser::new(None)
+                .parse("https://www.example.com/a\u{202E}gnp.exe")
+                .unwrap(),
+        );
+        assert_eq!(
+            report.findings,
+            vec![Finding::InvisibleCharacter {
+                component: "path",
+                character: '\u{202E}',
+            }]
+        );
+        assert_eq!(
+            report.findings[0].to_string(),
+            "path contains the invisible character U+202E"
+        );
+    }
+
+    #[test]
+    fn test_analyze_works_when_encoded_invisible_character() {
+        let report = analyze(
+            &Parser::new(None)
+                .parse("https://www.example.com/a%E2%80%AEgnp.exe?q=%e2%80%8b#x%E2%80%AE")
+                .unwrap(),
+        );
+        assert_eq!(
+            report.findings,
+            vec![
+                Finding::InvisibleCharacter {
+                    component: "path",
+                    character: '\u{202E}',
+                },
+                Finding::InvisibleCharacter {
+                    component: "query",
+                    character: '\u{200B}',
+                },
+                Finding::InvisibleCharacter {
+                    component: "anchor",
+                    character: '\u{202E}',
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_analyze_against_works_when_same_host() {
+        let report = analyze_against(
+            &Parser::new(None).parse("https://www.paypal.com/").unwrap(),
+            &["www.paypal.com"],
+        );
+        assert_eq!(report.risk(), Risk::None);
+        let report = analyze_against(
+            &Parser::new(None).parse("https://www.paypa1.com/").unwrap(),
+            &["www.paypal.com"],
+        );
+        assert_eq!(report.risk(), Risk::High);
+    }
+
+    #[test]
+    fn test_skeleton_works_when_fullwidth() {
+        assert_eq!(skeleton("ｇｏｏｇｌｅ"), "google");
+        assert_eq!(skeleton("ＧＯＯＧＬＥ"), skeleton("google"));
+    }
+
+    #[test]
+    fn test_decode_label_works_when_typical() {
+        assert_eq!(decode_label("xn--mnchen-3ya"), Some("münchen".to_string()));
+        assert_eq!(decode_label("xn--e1afmkfd"), Some("пример".to_string()));
+        assert_eq!(decode_label("example"), None);
+        assert_eq!(decode_label("xn--a-ecp.ru"), None);
+    }
+}
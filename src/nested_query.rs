@@ -0,0 +1,587 @@
+use crate::encoding::{decode_form, encode as encode_component, EncodeSet};
+use crate::error::ParseError;
+use crate::url::Url;
+
+/// A query decoded into a tree, as done by PHP, Rails or the `qs` package.
+///
+/// # Example
+/// ```rust
+/// use url_parse::nested_query::{decode, NestedQueryOptions, QueryValue};
+/// let tree = decode("filter[status][]=open&filter[owner]=me", &NestedQueryOptions::default()).unwrap();
+/// let filter = tree.get("filter").unwrap();
+/// assert_eq!(filter.get("owner").unwrap().as_str(), Some("me"));
+/// assert_eq!(
+///     filter.get("status").unwrap(),
+///     &QueryValue::Array(vec![QueryValue::Scalar("open".to_string())])
+/// );
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum QueryValue {
+    Scalar(String),
+    Array(Vec<QueryValue>),
+    /// Keys in order of first appearance.
+    Map(Vec<(String, QueryValue)>),
+}
+
+/// How nested keys are written.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeyStyle {
+    /// `filter[owner]=me`
+    Brackets,
+    /// `filter.owner=me`. Brackets are still understood when decoding.
+    Dots,
+}
+
+/// How arrays of scalars are written when encoding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ArrayStyle {
+    /// `ids[]=1&ids[]=2`
+    Brackets,
+    /// `ids[0]=1&ids[1]=2`
+    Indices,
+    /// `ids=1&ids=2`
+    Repeat,
+    /// `ids=1,2`
+    Comma,
+}
+
+/// Conventions and limits for decoding and encoding nested queries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NestedQueryOptions {
+    pub key_style: KeyStyle,
+    pub array_style: ArrayStyle,
+    /// Decode `ids=1&ids=2` to an array. Otherwise the last value wins.
+    pub repeated_keys: bool,
+    /// Decode `ids=1,2` to an array.
+    pub comma_lists: bool,
+    /// The maximum nesting below the top-level key.
+    pub max_depth: usize,
+    /// The maximum number of pairs in the query. With `comma_lists`, every item of a list counts as a pair.
+    pub max_params: usize,
+    /// The largest index of `ids[n]` treated as an array index, larger ones are map keys.
+    pub max_index: usize,
+}
+
+impl Default for NestedQueryOptions {
+    fn default() -> Self {
+        Self {
+            key_style: KeyStyle::Brackets,
+            array_style: ArrayStyle::Brackets,
+            repeated_keys: true,
+            comma_lists: false,
+            max_depth: 5,
+            max_params: 1000,
+            max_index: 20,
+        }
+    }
+}
+
+/// A value being decoded. Array items keep their index until all pairs are inserted, so that items sharing an index are
+/// merged and the array is ordered by index.
+#[derive(Debug)]
+enum Node {
+    Scalar(String),
+    Array(Vec<(usize, Node)>),
+    Map(Vec<(String, Node)>),
+}
+
+#[derive(Debug, PartialEq)]
+enum Token {
+    Key(String),
+    Index(usize),
+    Push,
+}
+
+impl QueryValue {
+    /// Get the value of a key of a map.
+    pub fn get(&self, key: &str) -> Option<&QueryValue> {
+        match self {
+            QueryValue::Map(entries) => entries.iter().find(|(k, _)| k == key).map(|(_, v)| v),
+            _ => None,
+        }
+    }
+
+    /// Get the string of a scalar.
+    pub fn as_str(&self) -> Option<&str> {
+        match self {
+            QueryValue::Scalar(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    /// Get the elements of an array.
+    pub fn as_array(&self) -> Option<&[QueryValue]> {
+        match self {
+            QueryValue::Array(v) => Some(v),
+            _ => None,
+        }
+    }
+}
+
+/// Decode a raw query, without the leading `?`, into a map.
+/// Fails if a limit is exceeded or a key is used both as a scalar and as a container.
+pub fn decode(query: &str, options: &NestedQueryOptions) -> Result<QueryValue, ParseError> {
+    let pairs: Vec<&str> = query.split('&').filter(|p| !p.is_empty()).collect();
+    if pairs.len() > options.max_params {
+        return Err(ParseError {});
+    }
+    let mut params = 0;
+    let mut root = Node::Map(vec![]);
+    for pair in pairs {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        params += match options.comma_lists {
+            true => value.split(',').count(),
+            false => 1,
+        };
+        if params > options.max_params {
+            return Err(ParseError {});
+        }
+        let value = match options.comma_lists && value.contains(',') {
+            true => Node::Array(
+                value
+                    .split(',')
+                    .enumerate()
+                    .map(|(i, v)| (i, Node::Scalar(decode_form(v))))
+                    .collect(),
+            ),
+            false => Node::Scalar(decode_form(value)),
+        };
+        let tokens = tokenize(&decode_form(key), options)?;
+        insert(&mut root, &tokens, value, options)?;
+    }
+    Ok(root.compact())
+}
+
+/// Encode a map as a query, without the leading `?`.
+///
+/// # Example
+/// ```rust
+/// use url_parse::nested_query::{decode, encode, ArrayStyle, KeyStyle, NestedQueryOptions};
+/// let tree = decode("user[name]=Ann+Lee&ids[]=1&ids[]=2", &NestedQueryOptions::default()).unwrap();
+/// let options = NestedQueryOptions {
+///     key_style: KeyStyle::Dots,
+///     array_style: ArrayStyle::Comma,
+///     ..NestedQueryOptions::default()
+/// };
+/// assert_eq!(encode(&tree, &options), "user.name=Ann+Lee&ids=1,2");
+/// ```
+pub fn encode(value: &QueryValue, options: &NestedQueryOptions) -> String {
+    let mut pairs = vec![];
+    if let QueryValue::Map(entries) = value {
+        for (key, value) in entries {
+            encode_value(&encode_text(key), value, options, &mut pairs);
+        }
+    }
+    pairs.join("&")
+}
+
+fn encode_text(key: &str) -> String {
+    encode_component(key, EncodeSet::FormUrlencoded)
+}
+
+fn encode_value(
+    prefix: &str,
+    value: &QueryValue,
+    options: &NestedQueryOptions,
+    out: &mut Vec<String>,
+) {
+    match value {
+        QueryValue::Scalar(v) => out.push(format!("{prefix}={}", encode_text(v))),
+        QueryValue::Map(entries) => {
+            for (key, value) in entries {
+                let key = match options.key_style {
+                    KeyStyle::Brackets => format!("{prefix}[{}]", encode_text(key)),
+                    KeyStyle::Dots => format!("{prefix}.{}", encode_text(key)),
+                };
+                encode_value(&key, value, options, out);
+            }
+        }
+        QueryValue::Array(items) => {
+            let scalars: Option<Vec<&str>> = items.iter().map(|v| v.as_str()).collect();
+            match (options.array_style, scalars) {
+                (ArrayStyle::Comma, Some(scalars)) => {
+                    let values: Vec<String> = scalars.iter().map(|v| encode_text(v)).collect();
+                    out.push(format!("{prefix}={}", values.join(",")));
+                }
+                (ArrayStyle::Repeat, Some(scalars)) => {
+                    for v in scalars {
+                        out.push(format!("{prefix}={}", encode_text(v)));
+                    }
+                }
+                (ArrayStyle::Brackets, Some(scalars)) => {
+                    for v in scalars {
+                        out.push(format!("{prefix}[]={}", encode_text(v)));
+                    }
+                }
+                _ => {
+                    for (i, item) in items.iter().enumerate() {
+                        encode_value(&format!("{prefix}[{i}]"), item, options, out);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Split `a[b][]` or `a.b` into tokens.
+fn tokenize(key: &str, options: &NestedQueryOptions) -> Result<Vec<Token>, ParseError> {
+    let root_end = match options.key_style {
+        KeyStyle::Brackets => key.find('['),
+        KeyStyle::Dots => key.find(['[', '.']),
+    };
+    let root_end = match root_end {
+        Some(0) | None => key.len(),
+        Some(v) => v,
+    };
+    let mut tokens = vec![Token::Key(key[..root_end].to_string())];
+    let mut rest = &key[root_end..];
+    while !rest.is_empty() {
+        let (name, next) = match rest.as_bytes()[0] {
+            b'[' => match rest.find(']') {
+                Some(end) => (&rest[1..end], &rest[end + 1..]),
+                None => return Err(ParseError {}),
+            },
+            _ => {
+                // Text after `]` which does not start a new segment is read as a key of its own.
+                let (rest, separators): (&str, &[char]) = match options.key_style {
+                    KeyStyle::Brackets => (rest, &['[']),
+                    KeyStyle::Dots => (rest.strip_prefix('.').unwrap_or(rest), &['[', '.']),
+                };
+                let end = rest.find(separators).unwrap_or(rest.len());
+                (&rest[..end], &rest[end..])
+            }
+        };
+        let token = match name.parse::<usize>() {
+            _ if name.is_empty() => Token::Push,
+            Ok(index) if index <= options.max_index => Token::Index(index),
+            _ => Token::Key(name.to_string()),
+        };
+        tokens.push(token);
+        if tokens.len() > options.max_depth + 1 {
+            return Err(ParseError {});
+        }
+        rest = next;
+    }
+    Ok(tokens)
+}
+
+fn insert(
+    node: &mut Node,
+    tokens: &[Token],
+    value: Node,
+    options: &NestedQueryOptions,
+) -> Result<(), ParseError> {
+    let (token, rest) = match tokens.split_first() {
+        Some(v) => v,
+        None => return Ok(()),
+    };
+    let (is_new, child) = match (token, node) {
+        (Token::Key(key), Node::Map(entries)) => slot(entries, key.to_string()),
+        (Token::Index(index), Node::Array(items)) => slot(items, *index),
+        (Token::Push, Node::Array(items)) if rest.is_empty() => {
+            append(items, value);
+            return Ok(());
+        }
+        (Token::Push, Node::Array(items)) => {
+            let index = next_index(items);
+            slot(items, index)
+        }
+        _ => return Err(ParseError {}),
+    };
+    if rest.is_empty() {
+        *child = match (is_new, std::mem::replace(child, Node::Map(vec![]))) {
+            (true, _) => value,
+            (false, Node::Array(mut items)) if options.repeated_keys => {
+                append(&mut items, value);
+                Node::Array(items)
+            }
+            (false, Node::Scalar(previous)) if options.repeated_keys => {
+                let mut items = vec![(0, Node::Scalar(previous))];
+                append(&mut items, value);
+                Node::Array(items)
+            }
+            (false, Node::Map(_)) => return Err(ParseError {}),
+            (false, _) => value,
+        };
+        return Ok(());
+    }
+    if is_new && !matches!(rest[0], Token::Key(_)) {
+        *child = Node::Array(vec![]);
+    }
+    insert(child, rest, value, options)
+}
+
+/// Find the entry with the given key, adding an empty map if there is none. Also tells whether the entry is new.
+fn slot<K: PartialEq>(entries: &mut Vec<(K, Node)>, key: K) -> (bool, &mut Node) {
+    match entries.iter().position(|(k, _)| *k == key) {
+        Some(position) => (false, &mut entries[position].1),
+        None => {
+            entries.push((key, Node::Map(vec![])));
+            let position = entries.len() - 1;
+            (true, &mut entries[position].1)
+        }
+    }
+}
+
+/// Add a value, or the items of an array, after the largest index.
+fn append(items: &mut Vec<(usize, Node)>, value: Node) {
+    let values = match value {
+        Node::Array(mut values) => {
+            values.sort_by_key(|(i, _)| *i);
+            values.into_iter().map(|(_, v)| v).collect()
+        }
+        value => vec![value],
+    };
+    for value in values {
+        let index = next_index(items);
+        items.push((index, value));
+    }
+}
+
+fn next_index(items: &[(usize, Node)]) -> usize {
+    items.iter().map(|(i, _)| i + 1).max().unwrap_or(0)
+}
+
+impl Node {
+    /// Convert into a `QueryValue`, ordering the items of arrays by index.
+    fn compact(self) -> QueryValue {
+        match self {
+            Node::Scalar(v) => QueryValue::Scalar(v),
+            Node::Array(mut items) => {
+                items.sort_by_key(|(i, _)| *i);
+                QueryValue::Array(items.into_iter().map(|(_, v)| v.compact()).collect())
+            }
+            Node::Map(entries) => {
+                QueryValue::Map(entries.into_iter().map(|(k, v)| (k, v.compact())).collect())
+            }
+        }
+    }
+}
+
+impl Url {
+    /// Decode the query of the url into a tree. See `nested_query::decode()`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// use url_parse::nested_query::NestedQueryOptions;
+    /// let url = Parser::new(None).parse("https://example.com/items?ids=1,2,3").unwrap();
+    /// let options = NestedQueryOptions {
+    ///     comma_lists: true,
+    ///     ..NestedQueryOptions::default()
+    /// };
+    /// let tree = url.query_tree(&options).unwrap();
+    /// assert_eq!(tree.get("ids").unwrap().as_array().unwrap().len(), 3);
+    /// ```
+    pub fn query_tree(&self, options: &NestedQueryOptions) -> Result<QueryValue, ParseError> {
+        decode(self.query.as_deref().unwrap_or_default(), options)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scalar(v: &str) -> QueryValue {
+        QueryValue::Scalar(v.to_string())
+    }
+
+    fn map(entries: Vec<(&str, QueryValue)>) -> QueryValue {
+        QueryValue::Map(
+            entries
+                .into_iter()
+                .map(|(k, v)| (k.to_string(), v))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn test_decode_works_when_brackets() {
+        let input = "filter[status][]=open&filter[status][]=closed&filter[owner]=me&a[0][x]=1&a[0][y]=2&a[1][x]=3";
+        let result = decode(input, &NestedQueryOptions::default()).unwrap();
+        assert_eq!(
+            result,
+            map(vec![
+                (
+                    "filter",
+                    map(vec![
+                        (
+                            "status",
+                            QueryValue::Array(vec![scalar("open"), scalar("closed")])
+                        ),
+                        ("owner", scalar("me")),
+                    ])
+                ),
+                (
+                    "a",
+                    QueryValue::Array(vec![
+                        map(vec![("x", scalar("1")), ("y", scalar("2"))]),
+                        map(vec![("x", scalar("3"))]),
+                    ])
+                ),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_decode_works_when_dots_and_encoded_brackets() {
+        let options = NestedQueryOptions {
+            key_style: KeyStyle::Dots,
+            ..NestedQueryOptions::default()
+        };
+        let result = decode("user.name=Ann&user.tags%5B%5D=x&user.tags[]=y", &options).unwrap();
+        assert_eq!(
+            result,
+            map(vec![(
+                "user",
+                map(vec![
+                    ("name", scalar("Ann")),
+                    ("tags", QueryValue::Array(vec![scalar("x"), scalar("y")])),
+                ])
+            )])
+        );
+    }
+
+    #[test]
+    fn test_decode_works_when_repeated_keys() {
+        let result = decode("a=1&a=2&b=3", &NestedQueryOptions::default()).unwrap();
+        assert_eq!(result.get("a").unwrap().as_array().unwrap().len(), 2);
+
+        let options = NestedQueryOptions {
+            repeated_keys: false,
+            ..NestedQueryOptions::default()
+        };
+        let result = decode("a=1&a=2", &options).unwrap();
+        assert_eq!(result.get("a"), Some(&scalar("2")));
+    }
+
+    #[test]
+    fn test_decode_works_when_indices_out_of_order() {
+        let options = NestedQueryOptions::default();
+        let result = decode("a[1]=c&a[0]=b", &options).unwrap();
+        assert_eq!(
+            result,
+            map(vec![(
+                "a",
+                QueryValue::Array(vec![scalar("b"), scalar("c")])
+            )])
+        );
+        let result = decode("a[5]=z&a[]=w&a[2]=y", &options).unwrap();
+        assert_eq!(
+            result.get("a").unwrap(),
+            &QueryValue::Array(vec![scalar("y"), scalar("z"), scalar("w")])
+        );
+    }
+
+    #[test]
+    fn test_decode_works_when_shared_index() {
+        let options = NestedQueryOptions::default();
+        let result = decode("a[1][x]=1&a[1][y]=2&a[0][x]=3", &options).unwrap();
+        assert_eq!(
+            result.get("a").unwrap(),
+            &QueryValue::Array(vec![
+                map(vec![("x", scalar("3"))]),
+                map(vec![("x", scalar("1")), ("y", scalar("2"))]),
+            ])
+        );
+        let result = decode("a[3][b][]=1&a[1]=x&a[3][b][]=2", &options).unwrap();
+        assert_eq!(
+            result.get("a").unwrap(),
+            &QueryValue::Array(vec![
+                scalar("x"),
+                map(vec![(
+                    "b",
+                    QueryValue::Array(vec![scalar("1"), scalar("2")])
+                )]),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_decode_works_when_large_index() {
+        let result = decode("a[100]=x", &NestedQueryOptions::default()).unwrap();
+        assert_eq!(result, map(vec![("a", map(vec![("100", scalar("x"))]))]));
+    }
+
+    #[test]
+    fn test_decode_works_when_text_after_bracket() {
+        let options = NestedQueryOptions::default();
+        let nested = |key: &str| map(vec![("a", map(vec![("b", map(vec![(key, scalar("1"))]))]))]);
+        assert_eq!(decode("a[b]é=1", &options).unwrap(), nested("é"));
+        assert_eq!(decode("a[b]xyz=1", &options).unwrap(), nested("xyz"));
+        assert_eq!(decode("a[b].c=1", &options).unwrap(), nested(".c"));
+
+        let options = NestedQueryOptions {
+            key_style: KeyStyle::Dots,
+            ..NestedQueryOptions::default()
+        };
+        assert_eq!(decode("a[b]é=1", &options).unwrap(), nested("é"));
+        assert_eq!(decode("a[b].xyz=1", &options).unwrap(), nested("xyz"));
+    }
+
+    #[test]
+    fn test_decode_fails_when_limits_exceeded() {
+        let options = NestedQueryOptions {
+            max_depth: 2,
+            max_params: 3,
+            ..NestedQueryOptions::default()
+        };
+        assert!(decode("a[b][c]=1", &options).is_ok());
+        assert!(decode("a[b][c][d]=1", &options).is_err());
+        assert!(decode("a=1&b=2&c=3&d=4", &options).is_err());
+    }
+
+    #[test]
+    fn test_decode_fails_when_comma_list_exceeds_max_params() {
+        let options = NestedQueryOptions {
+            comma_lists: true,
+            max_params: 3,
+            ..NestedQueryOptions::default()
+        };
+        assert!(decode("a=1,2&b=3", &options).is_ok());
+        assert!(decode("a=1,2,3,4", &options).is_err());
+        assert!(decode("a=1,2&b=3,4", &options).is_err());
+        let input = format!("a={}", vec!["1"; 100_000].join(","));
+        assert!(decode(&input, &options).is_err());
+    }
+
+    #[test]
+    fn test_decode_fails_when_conflicting_types() {
+        let options = NestedQueryOptions::default();
+        assert!(decode("a=1&a[b]=2", &options).is_err());
+        assert!(decode("a[b]=1&a=2", &options).is_err());
+        assert!(decode("a[b=1", &options).is_err());
+    }
+
+    #[test]
+    fn test_encode_works_when_each_array_style() {
+        let tree = decode("ids[]=1&ids[]=a,b&q=x y", &NestedQueryOptions::default()).unwrap();
+        let expected = [
+            (ArrayStyle::Brackets, "ids[]=1&ids[]=a%2Cb&q=x+y"),
+            (ArrayStyle::Indices, "ids[0]=1&ids[1]=a%2Cb&q=x+y"),
+            (ArrayStyle::Repeat, "ids=1&ids=a%2Cb&q=x+y"),
+            (ArrayStyle::Comma, "ids=1,a%2Cb&q=x+y"),
+        ];
+        for (array_style, expected) in expected {
+            let options = NestedQueryOptions {
+                array_style,
+                comma_lists: true,
+                ..NestedQueryOptions::default()
+            };
+            let result = encode(&tree, &options);
+            assert_eq!(result, expected);
+            assert_eq!(decode(&result, &options).unwrap(), tree);
+        }
+    }
+
+    #[test]
+    fn test_encode_works_when_array_of_maps() {
+        let input = "a[0][x]=1&a[1][x]=2";
+        let tree = decode(input, &NestedQueryOptions::default()).unwrap();
+        let options = NestedQueryOptions {
+            array_style: ArrayStyle::Comma,
+            ..NestedQueryOptions::default()
+        };
+        assert_eq!(encode(&tree, &options), input);
+    }
+}
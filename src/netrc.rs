@@ -0,0 +1,328 @@
+use crate::encoding::{encode, EncodeSet};
+use crate::error::ParseError;
+use crate::url::Url;
+use std::path::PathBuf;
+
+/// Credentials parsed from a `.netrc` file.
+///
+/// # Example
+/// ```rust
+/// use url_parse::core::Parser;
+/// use url_parse::netrc::Netrc;
+/// let netrc = Netrc::parse("machine github.com login mihai password s3cr3t").unwrap();
+/// let url = Parser::new(None).parse("https://github.com/mihaigalos/aim").unwrap();
+/// let result = netrc.apply(&url).unwrap();
+/// assert_eq!(result.username(), Some("mihai".to_string()));
+/// assert_eq!(result.password(), Some("s3cr3t".to_string()));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Netrc {
+    pub machines: Vec<NetrcEntry>,
+    pub default: Option<NetrcEntry>,
+}
+
+/// A single `machine` or `default` entry.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct NetrcEntry {
+    /// The host name, `None` for the `default` entry.
+    pub machine: Option<String>,
+    pub login: Option<String>,
+    pub password: Option<String>,
+    pub account: Option<String>,
+}
+
+impl Netrc {
+    /// Parse the contents of a `.netrc` file. Macro definitions (`macdef`) are skipped,
+    /// lines starting with `#` are comments and tokens can be quoted with `"`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::netrc::Netrc;
+    /// let input = "machine ftp.example.com login anonymous password \"my password\"\n\
+    ///              macdef init\ncd /pub\nbinary\n\n\
+    ///              default login guest";
+    /// let netrc = Netrc::parse(input).unwrap();
+    /// assert_eq!(netrc.machines[0].password, Some("my password".to_string()));
+    /// assert_eq!(netrc.default.unwrap().login, Some("guest".to_string()));
+    /// ```
+    pub fn parse(input: &str) -> Result<Self, ParseError> {
+        let mut netrc = Netrc::default();
+        let mut tokens = Tokenizer { input, pos: 0 };
+        let mut entry: Option<NetrcEntry> = None;
+
+        while let Some(token) = tokens.next_token()? {
+            match token.as_str() {
+                "machine" | "default" => {
+                    netrc.push(entry.take());
+                    let machine = match token.as_str() {
+                        "machine" => Some(tokens.next_token()?.ok_or(ParseError {})?),
+                        _ => None,
+                    };
+                    entry = Some(NetrcEntry {
+                        machine,
+                        ..NetrcEntry::default()
+                    });
+                }
+                "login" | "password" | "account" => {
+                    let value = tokens.next_token()?.ok_or(ParseError {})?;
+                    let entry = entry.as_mut().ok_or(ParseError {})?;
+                    match token.as_str() {
+                        "login" => entry.login = Some(value),
+                        "password" => entry.password = Some(value),
+                        _ => entry.account = Some(value),
+                    }
+                }
+                "macdef" => {
+                    tokens.next_token()?.ok_or(ParseError {})?;
+                    tokens.skip_macro();
+                }
+                _ => return Err(ParseError {}),
+            }
+        }
+        netrc.push(entry);
+        Ok(netrc)
+    }
+
+    /// Read and parse a `.netrc` file.
+    pub fn from_file(path: impl AsRef<std::path::Path>) -> std::io::Result<Self> {
+        Self::parse(&std::fs::read_to_string(path)?)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))
+    }
+
+    /// The location of the user's `.netrc` file: `$NETRC` if set, otherwise `.netrc` (`_netrc` on Windows)
+    /// in the home directory.
+    pub fn default_path() -> Option<PathBuf> {
+        if let Some(path) = std::env::var_os("NETRC") {
+            return Some(PathBuf::from(path));
+        }
+        let home = std::env::var_os("HOME").or_else(|| std::env::var_os("USERPROFILE"))?;
+        let name = match cfg!(windows) {
+            true => "_netrc",
+            false => ".netrc",
+        };
+        Some(PathBuf::from(home).join(name))
+    }
+
+    /// Find the entry for a host, falling back to the `default` entry.
+    pub fn find(&self, host: &str) -> Option<&NetrcEntry> {
+        self.machines
+            .iter()
+            .find(|e| {
+                e.machine
+                    .as_deref()
+                    .is_some_and(|m| m.eq_ignore_ascii_case(host))
+            })
+            .or(self.default.as_ref())
+    }
+
+    /// Fill in the credentials of a URL from the entry matching its host.
+    ///
+    /// A URL which already has a username only takes the password of an entry with the same login,
+    /// and a password already in the URL is kept.
+    /// Returns `None` if no entry applies.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// use url_parse::netrc::Netrc;
+    /// let netrc = Netrc::parse("machine example.com login alice password a\nmachine example.com login bob password b").unwrap();
+    /// let url = Parser::new(None).parse("https://bob@example.com/file").unwrap();
+    /// let result = netrc.apply(&url).unwrap();
+    /// assert_eq!(result.password(), Some("b".to_string()));
+    /// ```
+    pub fn apply(&self, url: &Url) -> Option<Url> {
+        let host = url.hostname()?;
+        let entry = match url.username() {
+            Some(user) => self
+                .machines
+                .iter()
+                .chain(self.default.iter())
+                .filter(|e| match &e.machine {
+                    Some(machine) => machine.eq_ignore_ascii_case(&host),
+                    None => true,
+                })
+                .find(|e| e.login.as_deref() == Some(user.as_str()))?,
+            None => self.find(&host)?,
+        };
+
+        let mut result = url.clone();
+        let login = entry.login.as_deref().map(|v| encode(v, EncodeSet::Component));
+        let password = entry.password.as_deref().map(|v| encode(v, EncodeSet::Component));
+        result.user_pass = match (&url.user_pass.0, login) {
+            (Some(user), _) => (Some(user.to_owned()), url.user_pass.1.clone().or(password)),
+            (None, Some(login)) => (Some(login), password),
+            (None, None) => return None,
+        };
+        Some(result)
+    }
+
+    fn push(&mut self, entry: Option<NetrcEntry>) {
+        match entry {
+            Some(v) if v.machine.is_some() => self.machines.push(v),
+            Some(v) => self.default = Some(v),
+            None => {}
+        }
+    }
+}
+
+struct Tokenizer<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl Tokenizer<'_> {
+    fn next_token(&mut self) -> Result<Option<String>, ParseError> {
+        loop {
+            let rest = &self.input[self.pos..];
+            let trimmed = rest.trim_start();
+            self.pos += rest.len() - trimmed.len();
+            if !trimmed.starts_with('#') {
+                break;
+            }
+            self.pos += trimmed.find('\n').unwrap_or(trimmed.len());
+        }
+
+        let mut chars = self.input[self.pos..].char_indices();
+        let mut token = String::new();
+        match chars.next() {
+            None => return Ok(None),
+            Some((_, '"')) => loop {
+                match chars.next() {
+                    Some((i, '"')) => {
+                        self.pos += i + 1;
+                        return Ok(Some(token));
+                    }
+                    Some((_, '\\')) => token.push(chars.next().ok_or(ParseError {})?.1),
+                    Some((_, c)) => token.push(c),
+                    None => return Err(ParseError {}),
+                }
+            },
+            Some((_, c)) => token.push(c),
+        }
+        for (i, c) in chars {
+            if c.is_whitespace() {
+                self.pos += i;
+                return Ok(Some(token));
+            }
+            token.push(c);
+        }
+        self.pos = self.input.len();
+        Ok(Some(token))
+    }
+
+    /// Skip a macro definition body, which ends at the first empty line.
+    fn skip_macro(&mut self) {
+        let rest = &self.input[self.pos..];
+        let start = rest.find('\n').map(|v| v + 1).unwrap_or(rest.len());
+        let end = rest[start..]
+            .split_inclusive('\n')
+            .scan(start, |pos, line| {
+                *pos += line.len();
+                Some((*pos, line.trim_end_matches(['\r', '\n']).is_empty()))
+            })
+            .find(|(_, empty)| *empty)
+            .map(|(pos, _)| pos)
+            .unwrap_or(rest.len());
+        self.pos += end;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Parser;
+
+    #[test]
+    fn test_parse_works_when_typical() {
+        let input = "# credentials\nmachine example.com\n  login user\n  password pass\n  account acc\n\nmachine other.org login x";
+        let result = Netrc::parse(input).unwrap();
+        assert_eq!(
+            result.machines,
+            vec![
+                NetrcEntry {
+                    machine: Some("example.com".to_string()),
+                    login: Some("user".to_string()),
+                    password: Some("pass".to_string()),
+                    account: Some("acc".to_string()),
+                },
+                NetrcEntry {
+                    machine: Some("other.org".to_string()),
+                    login: Some("x".to_string()),
+                    password: None,
+                    account: None,
+                }
+            ]
+        );
+        assert!(result.default.is_none());
+    }
+
+    #[test]
+    fn test_parse_works_when_macdef() {
+        let input = "machine a.com login a\nmacdef init\nmachine evil.com login b\nquit\n\nmachine c.com login c";
+        let result = Netrc::parse(input).unwrap();
+        let machines: Vec<Option<String>> =
+            result.machines.iter().map(|e| e.machine.clone()).collect();
+        assert_eq!(
+            machines,
+            vec![Some("a.com".to_string()), Some("c.com".to_string())]
+        );
+    }
+
+    #[test]
+    fn test_parse_works_when_quoted() {
+        let input = r#"machine a.com login "john doe" password "p\"w d""#;
+        let result = Netrc::parse(input).unwrap();
+        assert_eq!(result.machines[0].login, Some("john doe".to_string()));
+        assert_eq!(result.machines[0].password, Some("p\"w d".to_string()));
+    }
+
+    #[test]
+    fn test_parse_fails_when_invalid() {
+        assert!(Netrc::parse("machine").is_err());
+        assert!(Netrc::parse("login user").is_err());
+        assert!(Netrc::parse("machine a.com foo bar").is_err());
+        assert!(Netrc::parse("machine a.com login \"open").is_err());
+    }
+
+    #[test]
+    fn test_apply_works_when_default() {
+        let netrc = Netrc::parse("machine a.com login a\ndefault login anonymous password me@home")
+            .unwrap();
+        let url = Parser::new(None).parse("ftp://ftp.example.com/pub").unwrap();
+        let result = netrc.apply(&url).unwrap();
+        assert_eq!(
+            result.user_pass,
+            (Some("anonymous".to_string()), Some("me%40home".to_string()))
+        );
+        assert_eq!(result.password(), Some("me@home".to_string()));
+    }
+
+    #[test]
+    fn test_apply_works_when_host_case_differs() {
+        let netrc = Netrc::parse("machine GitHub.com login u password p").unwrap();
+        let url = Parser::new(None).parse("https://github.com/").unwrap();
+        let result = netrc.apply(&url).unwrap();
+        assert_eq!(result.username(), Some("u".to_string()));
+    }
+
+    #[test]
+    fn test_apply_is_none_when_no_match() {
+        let netrc = Netrc::parse("machine a.com login a password b").unwrap();
+        let parser = Parser::new(None);
+        assert!(netrc.apply(&parser.parse("https://b.com/").unwrap()).is_none());
+        assert!(netrc.apply(&parser.parse("https://other@a.com/").unwrap()).is_none());
+    }
+
+    #[test]
+    fn test_apply_works_when_url_has_password() {
+        let netrc = Netrc::parse("machine a.com login bob password netrc\nmachine b.com login bob")
+            .unwrap();
+        let parser = Parser::new(None);
+        let result = netrc.apply(&parser.parse("https://bob:pw@a.com/").unwrap()).unwrap();
+        assert_eq!(result.password(), Some("pw".to_string()));
+        let result = netrc.apply(&parser.parse("https://bob:pw@b.com/").unwrap()).unwrap();
+        assert_eq!(result.password(), Some("pw".to_string()));
+        let result = netrc.apply(&parser.parse("https://bob@a.com/").unwrap()).unwrap();
+        assert_eq!(result.password(), Some("netrc".to_string()));
+    }
+}
@@ -0,0 +1,276 @@
+use crate::encoding::decode_utf8_lossy;
+use crate::error::ParseError;
+use crate::host_matcher::HostMatcher;
+use crate::url::path::normalize_segments;
+use crate::url::Url;
+
+/// Maps public URLs to upstream URLs, as done by a reverse proxy.
+///
+/// A mapping applies when the host matches and the path starts with its prefix on a segment boundary.
+/// Dot segments (`..`, `%2e%2e`, ...) are removed from the path before matching and are never forwarded.
+/// The mapping with the longest prefix wins; remaining ties go to host-constrained mappings, then to the one inserted first.
+/// The rewritten URL takes scheme, credentials, host and port from the upstream, replaces the prefix by the
+/// upstream path and keeps the rest of the path, the query and the anchor.
+///
+/// # Example
+/// ```rust
+/// use url_parse::core::Parser;
+/// use url_parse::proxy::{ProxyMapping, ProxyTable};
+/// let parser = Parser::new(None);
+/// let mut table = ProxyTable::new();
+/// table.insert(ProxyMapping {
+///     hosts: Some(vec!["example.com".to_string()]),
+///     prefix: "/api".to_string(),
+///     upstream: parser.parse("http://backend.internal:8080/v1").unwrap(),
+/// }).unwrap();
+/// table.insert(ProxyMapping {
+///     hosts: None,
+///     prefix: "/".to_string(),
+///     upstream: parser.parse("http://static.internal").unwrap(),
+/// }).unwrap();
+///
+/// let url = parser.parse("https://example.com/api/users?page=2#top").unwrap();
+/// let result = table.rewrite(&url).unwrap();
+/// assert_eq!(result.serialize(), "http://backend.internal:8080/v1/users?page=2#top");
+///
+/// let url = parser.parse("https://example.com/apis/index.html").unwrap();
+/// let result = table.rewrite(&url).unwrap();
+/// assert_eq!(result.serialize(), "http://static.internal/apis/index.html");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct ProxyTable {
+    entries: Vec<Entry>,
+}
+
+/// A single prefix mapping of a `ProxyTable`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ProxyMapping {
+    /// Host patterns the public URL must match, see `HostMatcher`. `None` matches any host.
+    pub hosts: Option<Vec<String>>,
+    /// The path prefix, i.e. `/api`. `/` matches every path.
+    pub prefix: String,
+    /// Where matching URLs are sent. Its path replaces the prefix; an empty path strips it.
+    pub upstream: Url,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    mapping: ProxyMapping,
+    hosts: Option<HostMatcher>,
+    prefix: Vec<String>,
+}
+
+impl ProxyTable {
+    /// Create an empty table.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a mapping. Fails if a host pattern is invalid or the prefix does not start with `/`.
+    pub fn insert(&mut self, mapping: ProxyMapping) -> Result<(), ParseError> {
+        let hosts = match &mapping.hosts {
+            Some(patterns) => Some(HostMatcher::from_patterns(
+                patterns.iter().map(|p| p.as_str()),
+            )?),
+            None => None,
+        };
+        let prefix = mapping.prefix.strip_prefix('/').ok_or(ParseError {})?;
+        let prefix = prefix
+            .split('/')
+            .filter(|s| !s.is_empty())
+            .map(|s| s.to_string())
+            .collect();
+        self.entries.push(Entry {
+            mapping,
+            hosts,
+            prefix,
+        });
+        Ok(())
+    }
+
+    /// Find the mapping which applies to the URL.
+    pub fn find(&self, url: &Url) -> Option<&ProxyMapping> {
+        self.lookup(url).map(|entry| &entry.mapping)
+    }
+
+    /// Rewrite a public URL to its upstream URL. Returns `None` if no mapping applies.
+    pub fn rewrite(&self, url: &Url) -> Option<Url> {
+        let entry = self.lookup(url)?;
+        let upstream = &entry.mapping.upstream;
+
+        let mut path: Vec<String> = upstream.path.clone().unwrap_or_default();
+        if path.last().is_some_and(|s| s.is_empty()) {
+            path.pop();
+        }
+        path.extend(request_path(url).into_iter().skip(entry.prefix.len()));
+
+        Some(Url {
+            scheme: upstream.scheme.clone(),
+            user_pass: upstream.user_pass.clone(),
+            subdomain: upstream.subdomain.clone(),
+            domain: upstream.domain.clone(),
+            top_level_domain: upstream.top_level_domain.clone(),
+            port: upstream.port,
+            default_port: upstream.default_port,
+            path: match path.is_empty() {
+                true => None,
+                false => Some(path),
+            },
+            query: url.query.clone(),
+            anchor: url.anchor.clone(),
+        })
+    }
+
+    fn lookup(&self, url: &Url) -> Option<&Entry> {
+        let path: Vec<String> = request_path(url)
+            .iter()
+            .map(|s| decode_utf8_lossy(s))
+            .collect();
+        self.entries
+            .iter()
+            .filter(|entry| entry.hosts.as_ref().is_none_or(|h| h.matches(url)))
+            .filter(|entry| path.starts_with(&entry.prefix))
+            .min_by_key(|entry| (std::cmp::Reverse(entry.prefix.len()), entry.hosts.is_none()))
+    }
+}
+
+/// The path segments of the URL as written, with dot segments removed so that a request cannot leave its prefix.
+fn request_path(url: &Url) -> Vec<String> {
+    normalize_segments(url.path.as_deref().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Parser;
+
+    fn mapping(hosts: Option<&str>, prefix: &str, upstream: &str) -> ProxyMapping {
+        ProxyMapping {
+            hosts: hosts.map(|h| vec![h.to_string()]),
+            prefix: prefix.to_string(),
+            upstream: Parser::new(None).parse(upstream).unwrap(),
+        }
+    }
+
+    #[test]
+    fn test_rewrite_works_when_longest_prefix() {
+        let mut table = ProxyTable::new();
+        table.insert(mapping(None, "/api", "http://a")).unwrap();
+        table
+            .insert(mapping(None, "/api/v2/", "http://b/"))
+            .unwrap();
+
+        let result = table
+            .rewrite(
+                &Parser::new(None)
+                    .parse("https://x.com/api/v2/items")
+                    .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(result.serialize(), "http://b/items");
+        let result = table
+            .rewrite(
+                &Parser::new(None)
+                    .parse("https://x.com/api/v1/items")
+                    .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(result.serialize(), "http://a/v1/items");
+    }
+
+    #[test]
+    fn test_rewrite_works_when_trailing_slash_and_no_rest() {
+        let mut table = ProxyTable::new();
+        table
+            .insert(mapping(None, "/app", "http://b:81/srv"))
+            .unwrap();
+
+        let result = table
+            .rewrite(&Parser::new(None).parse("https://x.com/app").unwrap())
+            .unwrap();
+        assert_eq!(result.serialize(), "http://b:81/srv");
+        let result = table
+            .rewrite(&Parser::new(None).parse("https://x.com/app/").unwrap())
+            .unwrap();
+        assert_eq!(result.serialize(), "http://b:81/srv/");
+    }
+
+    #[test]
+    fn test_rewrite_works_when_host_constrained() {
+        let mut table = ProxyTable::new();
+        table.insert(mapping(None, "/", "http://default")).unwrap();
+        table
+            .insert(mapping(Some("*.example.com"), "/", "http://tenant"))
+            .unwrap();
+
+        let result = table
+            .rewrite(&Parser::new(None).parse("https://eu.example.com/a").unwrap())
+            .unwrap();
+        assert_eq!(result.serialize(), "http://tenant/a");
+        let result = table
+            .rewrite(&Parser::new(None).parse("https://example.org/a").unwrap())
+            .unwrap();
+        assert_eq!(result.serialize(), "http://default/a");
+    }
+
+    #[test]
+    fn test_rewrite_is_none_when_no_mapping() {
+        let mut table = ProxyTable::new();
+        table
+            .insert(mapping(Some("example.com"), "/api", "http://a"))
+            .unwrap();
+        assert!(table
+            .rewrite(&Parser::new(None).parse("https://example.com/apis").unwrap())
+            .is_none());
+        assert!(table
+            .rewrite(&Parser::new(None).parse("https://other.com/api").unwrap())
+            .is_none());
+        assert!(table.insert(mapping(None, "api", "http://a")).is_err());
+    }
+
+    #[test]
+    fn test_rewrite_works_when_dot_segments() {
+        let mut table = ProxyTable::new();
+        table
+            .insert(mapping(None, "/api", "http://backend:8080/v1"))
+            .unwrap();
+        table
+            .insert(mapping(None, "/public", "http://static/files"))
+            .unwrap();
+
+        assert!(table
+            .rewrite(
+                &Parser::new(None)
+                    .parse("https://x.com/api/../admin")
+                    .unwrap()
+            )
+            .is_none());
+        assert!(table
+            .rewrite(
+                &Parser::new(None)
+                    .parse("https://x.com/public/%2e%2e/%2e%2e/secret")
+                    .unwrap()
+            )
+            .is_none());
+        assert!(table
+            .rewrite(
+                &Parser::new(None)
+                    .parse("https://x.com/public/%2E./secret")
+                    .unwrap()
+            )
+            .is_none());
+
+        let result = table
+            .rewrite(
+                &Parser::new(None)
+                    .parse("https://x.com/api/a/%2E./b/./c")
+                    .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(result.serialize(), "http://backend:8080/v1/b/c");
+        let result = table
+            .rewrite(&Parser::new(None).parse("https://x.com/api/a/..").unwrap())
+            .unwrap();
+        assert_eq!(result.serialize(), "http://backend:8080/v1/");
+    }
+}
@@ -0,0 +1,292 @@
+use crate::core::Parser;
+use crate::encoding::{decode_form, decode_utf8_lossy};
+use crate::error::ParseError;
+use crate::host_matcher::HostMatcher;
+use crate::url::Url;
+
+/// Unwraps links wrapped by redirectors, i.e. `https://www.google.com/url?q=...`.
+///
+/// A redirector is recognized by its host and path; its target is taken from the first of its query parameters
+/// holding an `http` or `https` URL. Targets are unwrapped again until no redirector applies or `max_depth` is reached.
+/// Google, Outlook SafeLinks, Facebook and YouTube redirectors are built in.
+///
+/// # Example
+/// ```rust
+/// use url_parse::core::Parser;
+/// use url_parse::redirector::{Redirector, Unwrapper};
+/// let mut unwrapper = Unwrapper::builtin();
+/// unwrapper.insert(Redirector {
+///     name: "mail".to_string(),
+///     hosts: vec!["click.mail.example.com".to_string()],
+///     path: Some("/track".to_string()),
+///     params: vec!["target".to_string()],
+/// }).unwrap();
+///
+/// let input = "https://click.mail.example.com/track?target=https%3A%2F%2Fwww.google.com%2Furl%3Fq%3Dhttps%253A%252F%252Fexample.org%252Fa%253Fb%253D1%26sa%3DD";
+/// let url = Parser::new(None).parse(input).unwrap();
+/// let result = unwrapper.resolve(&url);
+/// assert_eq!(result.url.serialize(), "https://example.org/a?b=1");
+/// assert_eq!(result.hops, vec!["mail", "google"]);
+/// assert!(!result.truncated);
+/// ```
+#[derive(Debug, Clone)]
+pub struct Unwrapper {
+    entries: Vec<Entry>,
+    max_depth: usize,
+}
+
+/// The definition of a redirector.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Redirector {
+    /// A name for reporting, i.e. `google`.
+    pub name: String,
+    /// Host patterns the URL must match, see `HostMatcher`.
+    pub hosts: Vec<String>,
+    /// The path the URL must have, i.e. `/url`. `None` matches any path.
+    pub path: Option<String>,
+    /// The query keys which may hold the target, tried in order.
+    pub params: Vec<String>,
+}
+
+/// The result of unwrapping a URL.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Unwrapped {
+    /// The innermost target, or the input if no redirector applies.
+    pub url: Url,
+    /// The names of the redirectors unwrapped, from the outside in.
+    pub hops: Vec<String>,
+    /// Whether unwrapping stopped at `max_depth` while a redirector still applied.
+    pub truncated: bool,
+}
+
+#[derive(Debug, Clone)]
+struct Entry {
+    redirector: Redirector,
+    hosts: HostMatcher,
+}
+
+/// A built-in redirector as name, host patterns, path and query keys.
+type BuiltinRedirector = (
+    &'static str,
+    &'static [&'static str],
+    Option<&'static str>,
+    &'static [&'static str],
+);
+
+const REDIRECTORS: &[BuiltinRedirector] = &[
+    (
+        "google",
+        &["google.com", "*.google.com"],
+        Some("/url"),
+        &["q", "url"],
+    ),
+    (
+        "outlook",
+        &["*.safelinks.protection.outlook.com"],
+        None,
+        &["url"],
+    ),
+    (
+        "facebook",
+        &["l.facebook.com", "lm.facebook.com", "l.messenger.com"],
+        Some("/l.php"),
+        &["u"],
+    ),
+    (
+        "youtube",
+        &["youtube.com", "*.youtube.com"],
+        Some("/redirect"),
+        &["q"],
+    ),
+];
+
+impl Default for Unwrapper {
+    fn default() -> Self {
+        Self {
+            entries: vec![],
+            max_depth: 5,
+        }
+    }
+}
+
+impl Unwrapper {
+    /// Create an unwrapper without redirectors, unwrapping at most 5 levels.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create an unwrapper with the built-in redirectors.
+    pub fn builtin() -> Self {
+        let mut unwrapper = Self::new();
+        for (name, hosts, path, params) in REDIRECTORS {
+            let redirector = Redirector {
+                name: name.to_string(),
+                hosts: hosts.iter().map(|h| h.to_string()).collect(),
+                path: path.map(|p| p.to_string()),
+                params: params.iter().map(|p| p.to_string()).collect(),
+            };
+            unwrapper
+                .insert(redirector)
+                .expect("valid built-in redirectors");
+        }
+        unwrapper
+    }
+
+    /// Set the maximum number of redirectors unwrapped from a single URL.
+    pub fn with_max_depth(mut self, max_depth: usize) -> Self {
+        self.max_depth = max_depth;
+        self
+    }
+
+    /// Add a redirector. Fails if a host pattern is invalid.
+    pub fn insert(&mut self, redirector: Redirector) -> Result<(), ParseError> {
+        let hosts = HostMatcher::from_patterns(redirector.hosts.iter().map(|h| h.as_str()))?;
+        self.entries.push(Entry { redirector, hosts });
+        Ok(())
+    }
+
+    /// Unwrap the URL, following nested redirectors up to the maximum depth.
+    pub fn resolve(&self, url: &Url) -> Unwrapped {
+        let parser = Parser::new(None);
+        let mut result = Unwrapped {
+            url: url.clone(),
+            hops: vec![],
+            truncated: false,
+        };
+        while let Some((name, target)) = self.target(&parser, &result.url) {
+            if result.hops.len() == self.max_depth {
+                result.truncated = true;
+                break;
+            }
+            result.hops.push(name.to_string());
+            result.url = target;
+        }
+        result
+    }
+
+    /// Find the target of the first redirector which applies to the URL.
+    fn target(&self, parser: &Parser, url: &Url) -> Option<(&str, Url)> {
+        let path = format!("/{}", url.path.as_deref().unwrap_or_default().join("/"));
+        self.entries
+            .iter()
+            .filter(|entry| entry.hosts.matches(url))
+            .filter(|entry| entry.redirector.path.as_ref().is_none_or(|p| *p == path))
+            .find_map(|entry| {
+                let target = entry.redirector.params.iter().find_map(|param| {
+                    let value = decode_utf8_lossy(raw_query_value(url, param)?);
+                    let target = parser.parse(&value).ok()?;
+                    let scheme = target.scheme.as_deref()?.to_ascii_lowercase();
+                    match (scheme.as_str(), &target.domain) {
+                        ("http" | "https", Some(_)) => Some(target),
+                        _ => None,
+                    }
+                })?;
+                Some((entry.redirector.name.as_str(), target))
+            })
+    }
+}
+
+/// Find the undecoded value of the first query pair with the given key. Unlike `QueryPairs`, a `+` in the value is
+/// kept, as it is part of the target URL rather than an encoded space.
+fn raw_query_value<'a>(url: &'a Url, key: &str) -> Option<&'a str> {
+    url.query
+        .as_deref()?
+        .split('&')
+        .map(|pair| pair.split_once('=').unwrap_or((pair, "")))
+        .find(|(k, _)| decode_form(k) == key)
+        .map(|(_, v)| v)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_resolve_works_when_builtin() {
+        let unwrapper = Unwrapper::builtin();
+        let cases = [
+            (
+                "https://www.google.com/url?sa=t&url=https%3A%2F%2Fexample.com%2Fa%23top&usg=x",
+                "https://example.com/a#top",
+                "google",
+            ),
+            (
+                "https://eur01.safelinks.protection.outlook.com/?url=http%3A%2F%2Fexample.com%2F%3Fa%3D1&data=05",
+                "http://example.com/?a=1",
+                "outlook",
+            ),
+            (
+                "https://l.facebook.com/l.php?u=https%3A%2F%2Fexample.org%2F&h=AT0",
+                "https://example.org/",
+                "facebook",
+            ),
+        ];
+        for (input, expected, name) in cases {
+            let result = unwrapper.resolve(&Parser::new(None).parse(input).unwrap());
+            assert_eq!(result.url.serialize(), expected);
+            assert_eq!(result.hops, vec![name]);
+        }
+    }
+
+    #[test]
+    fn test_resolve_works_when_unencoded_target() {
+        let input = "https://www.google.com/url?q=https://example.com/a&sa=D";
+        let result = Unwrapper::builtin().resolve(&Parser::new(None).parse(input).unwrap());
+        assert_eq!(result.url.serialize(), "https://example.com/a");
+    }
+
+    #[test]
+    fn test_resolve_works_when_plus_in_target() {
+        let unwrapper = Unwrapper::builtin();
+        let input = "https://www.google.com/url?q=https://example.com/a?x=1+2";
+        let result = unwrapper.resolve(&Parser::new(None).parse(input).unwrap());
+        assert_eq!(result.url.serialize(), "https://example.com/a?x=1+2");
+
+        let input = "https://www.google.com/url?q=https%3A%2F%2Fexample.com%2Fa%3Fx%3D1%2B2+3";
+        let result = unwrapper.resolve(&Parser::new(None).parse(input).unwrap());
+        assert_eq!(result.url.serialize(), "https://example.com/a?x=1+2+3");
+    }
+
+    #[test]
+    fn test_resolve_is_unchanged_when_no_redirector() {
+        let unwrapper = Unwrapper::builtin();
+        for input in [
+            "https://www.google.com/search?q=https%3A%2F%2Fexample.com",
+            "https://www.google.com/url?q=rust+url",
+            "https://www.google.com/url?q=javascript%3Aalert(1)",
+            "https://example.com/l.php?u=https%3A%2F%2Fexample.org",
+        ] {
+            let url = Parser::new(None).parse(input).unwrap();
+            let result = unwrapper.resolve(&url);
+            assert_eq!(result.url, url);
+            assert!(result.hops.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_resolve_works_when_max_depth_reached() {
+        let inner = "https%3A%2F%2Fl.facebook.com%2Fl.php%3Fu%3Dhttps%253A%252F%252Fexample.com";
+        let input = format!("https://www.google.com/url?q={inner}");
+        let unwrapper = Unwrapper::builtin().with_max_depth(1);
+        let result = unwrapper.resolve(&Parser::new(None).parse(&input).unwrap());
+        assert_eq!(result.hops, vec!["google"]);
+        assert!(result.truncated);
+        assert_eq!(
+            result.url.serialize(),
+            "https://l.facebook.com/l.php?u=https%3A%2F%2Fexample.com"
+        );
+
+        let result = Unwrapper::builtin().resolve(&Parser::new(None).parse(&input).unwrap());
+        assert_eq!(result.hops, vec!["google", "facebook"]);
+        assert!(!result.truncated);
+    }
+
+    #[test]
+    fn test_insert_fails_when_invalid_host() {
+        let redirector = Redirector {
+            hosts: vec!["".to_string()],
+            ..Redirector::default()
+        };
+        assert!(Unwrapper::new().insert(redirector).is_err());
+    }
+}
@@ -0,0 +1,403 @@
+use crate::encoding::decode_utf8_lossy;
+use crate::error::ParseError;
+use crate::host_matcher::HostMatcher;
+use crate::url::path::normalize_segments;
+use crate::url::Url;
+use std::collections::HashMap;
+
+/// Maps URLs to values (i.e. handlers) by path templates.
+///
+/// Templates are made of static segments, `{name}` parameters matching exactly one segment and
+/// an optional final `{*name}` wildcard matching the remaining segments, possibly none.
+/// When several routes match, the one whose segments are the most specific from left to right wins:
+/// a static segment beats a parameter, which beats a wildcard. Remaining ties go to routes with scheme or
+/// host constraints, then to the route inserted first. A trailing slash is ignored and dot segments are removed before
+/// matching, so `/public/../admin` is matched as `/admin`.
+///
+/// # Example
+/// ```rust
+/// use url_parse::core::Parser;
+/// use url_parse::router::Router;
+/// let mut router = Router::new();
+/// router.insert("/users/{id}/repos/{repo}", "repo").unwrap();
+/// router.insert("/users/me/repos/{repo}", "own repo").unwrap();
+/// router.insert("/static/{*rest}", "static").unwrap();
+///
+/// let url = Parser::new(None).parse("https://example.com/users/42/repos/url-parse").unwrap();
+/// let result = router.matches(&url).unwrap();
+/// assert_eq!(*result.route.value(), "repo");
+/// assert_eq!(result.params["id"], "42");
+/// assert_eq!(result.params["repo"], "url-parse");
+///
+/// let url = Parser::new(None).parse("https://example.com/users/me/repos/aim").unwrap();
+/// assert_eq!(*router.matches(&url).unwrap().route.value(), "own repo");
+///
+/// let url = Parser::new(None).parse("https://example.com/static/css/site.css").unwrap();
+/// assert_eq!(router.matches(&url).unwrap().params["rest"], "css/site.css");
+/// ```
+#[derive(Debug, Clone)]
+pub struct Router<T> {
+    routes: Vec<Route<T>>,
+}
+
+/// Restricts a route to URLs with a given scheme and host.
+#[derive(Debug, Clone, Default)]
+pub struct RouteOptions {
+    /// The scheme the URL must have, compared case-insensitively.
+    pub scheme: Option<String>,
+    /// Host patterns the URL must match, see `HostMatcher`.
+    pub hosts: Option<Vec<String>>,
+}
+
+/// A compiled path template with its constraints and value.
+#[derive(Debug, Clone)]
+pub struct Route<T> {
+    template: String,
+    segments: Vec<Segment>,
+    scheme: Option<String>,
+    hosts: Option<HostMatcher>,
+    value: T,
+}
+
+/// A successful match of a URL against a router.
+#[derive(Debug, PartialEq)]
+pub struct RouteMatch<'a, T> {
+    pub route: &'a Route<T>,
+    /// The percent-decoded values of the template parameters. Wildcards join their segments as written with `/`, so that
+    /// an encoded `/` within a segment stays apart from the separators.
+    pub params: HashMap<String, String>,
+}
+
+/// The segment kinds in order of decreasing priority.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Segment {
+    Static(String),
+    Param(String),
+    Wildcard(String),
+}
+
+impl<T> Default for Router<T> {
+    fn default() -> Self {
+        Self { routes: vec![] }
+    }
+}
+
+impl<T> Router<T> {
+    /// Create an empty router.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Add a route matching any scheme and host. Fails if the template is invalid.
+    pub fn insert(&mut self, template: &str, value: T) -> Result<(), ParseError> {
+        self.insert_with(template, RouteOptions::default(), value)
+    }
+
+    /// Add a route constrained by scheme and host.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// use url_parse::router::{RouteOptions, Router};
+    /// let mut router = Router::new();
+    /// let options = RouteOptions {
+    ///     scheme: Some("https".to_string()),
+    ///     hosts: Some(vec!["*.example.com".to_string()]),
+    /// };
+    /// router.insert_with("/api/{*rest}", options, 1).unwrap();
+    /// router.insert("/api/{*rest}", 2).unwrap();
+    ///
+    /// let parser = Parser::new(None);
+    /// let url = parser.parse("https://eu.example.com/api/v1").unwrap();
+    /// assert_eq!(*router.matches(&url).unwrap().route.value(), 1);
+    /// let url = parser.parse("http://eu.example.com/api/v1").unwrap();
+    /// assert_eq!(*router.matches(&url).unwrap().route.value(), 2);
+    /// ```
+    pub fn insert_with(
+        &mut self,
+        template: &str,
+        options: RouteOptions,
+        value: T,
+    ) -> Result<(), ParseError> {
+        let hosts = match &options.hosts {
+            Some(patterns) => Some(HostMatcher::from_patterns(
+                patterns.iter().map(|p| p.as_str()),
+            )?),
+            None => None,
+        };
+        self.routes.push(Route {
+            template: template.to_string(),
+            segments: Self::compile(template)?,
+            scheme: options.scheme,
+            hosts,
+            value,
+        });
+        Ok(())
+    }
+
+    /// Find the most specific route matching the URL.
+    pub fn matches(&self, url: &Url) -> Option<RouteMatch<'_, T>> {
+        let mut raw = normalize_segments(url.path.as_deref().unwrap_or_default());
+        if raw.last().is_some_and(|s| s.is_empty()) {
+            raw.pop();
+        }
+        let path: Vec<String> = raw.iter().map(|s| decode_utf8_lossy(s)).collect();
+
+        self.routes
+            .iter()
+            .filter(|route| route.accepts(url))
+            .filter_map(|route| route.capture(&raw, &path).map(|params| (route, params)))
+            .min_by(|(a, _), (b, _)| {
+                a.segments
+                    .iter()
+                    .map(Segment::rank)
+                    .cmp(b.segments.iter().map(Segment::rank))
+                    .then_with(|| b.constraints().cmp(&a.constraints()))
+            })
+            .map(|(route, params)| RouteMatch { route, params })
+    }
+
+    fn compile(template: &str) -> Result<Vec<Segment>, ParseError> {
+        let template = template.strip_prefix('/').ok_or(ParseError {})?;
+        let template = template.strip_suffix('/').unwrap_or(template);
+        if template.is_empty() {
+            return Ok(vec![]);
+        }
+        let parts: Vec<&str> = template.split('/').collect();
+        let mut names = vec![];
+        let mut segments = vec![];
+        for (i, part) in parts.iter().enumerate() {
+            let segment = match part.strip_prefix('{').and_then(|p| p.strip_suffix('}')) {
+                Some(name) => match name.strip_prefix('*') {
+                    Some(name) if i + 1 == parts.len() => Segment::Wildcard(name.to_string()),
+                    Some(_) => return Err(ParseError {}),
+                    None => Segment::Param(name.to_string()),
+                },
+                None if part.contains(['{', '}']) => return Err(ParseError {}),
+                None => Segment::Static(part.to_string()),
+            };
+            if let Segment::Param(name) | Segment::Wildcard(name) = &segment {
+                if name.is_empty() || names.contains(name) {
+                    return Err(ParseError {});
+                }
+                names.push(name.to_string());
+            }
+            segments.push(segment);
+        }
+        Ok(segments)
+    }
+}
+
+impl<T> Route<T> {
+    /// The template this route was created from.
+    pub fn template(&self) -> &str {
+        &self.template
+    }
+
+    /// The value associated with this route.
+    pub fn value(&self) -> &T {
+        &self.value
+    }
+
+    fn accepts(&self, url: &Url) -> bool {
+        let scheme = match (&self.scheme, &url.scheme) {
+            (Some(expected), Some(actual)) => expected.eq_ignore_ascii_case(actual),
+            (Some(_), None) => false,
+            (None, _) => true,
+        };
+        let host = match &self.hosts {
+            Some(hosts) => hosts.matches(url),
+            None => true,
+        };
+        scheme && host
+    }
+
+    fn capture(&self, raw: &[String], path: &[String]) -> Option<HashMap<String, String>> {
+        let mut params = HashMap::new();
+        for (i, segment) in self.segments.iter().enumerate() {
+            match segment {
+                Segment::Static(expected) => {
+                    if path.get(i) != Some(expected) {
+                        return None;
+                    }
+                }
+                Segment::Param(name) => {
+                    params.insert(name.to_string(), path.get(i)?.to_string());
+                }
+                Segment::Wildcard(name) => {
+                    let rest = raw.get(i..).unwrap_or_default().join("/");
+                    params.insert(name.to_string(), rest);
+                    return Some(params);
+                }
+            }
+        }
+        match path.len() == self.segments.len() {
+            true => Some(params),
+            false => None,
+        }
+    }
+
+    fn constraints(&self) -> usize {
+        self.scheme.iter().count() + self.hosts.iter().count()
+    }
+}
+
+impl<T: PartialEq> PartialEq for Route<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.template == other.template
+            && self.scheme == other.scheme
+            && self.hosts == other.hosts
+            && self.value == other.value
+    }
+}
+
+impl Segment {
+    fn rank(&self) -> u8 {
+        match self {
+            Segment::Static(_) => 0,
+            Segment::Param(_) => 1,
+            Segment::Wildcard(_) => 2,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Parser;
+
+    #[test]
+    fn test_insert_fails_when_invalid_template() {
+        let mut router = Router::new();
+        assert!(router.insert("users/{id}", ()).is_err());
+        assert!(router.insert("/users/{}", ()).is_err());
+        assert!(router.insert("/users/{id}/{id}", ()).is_err());
+        assert!(router.insert("/{*rest}/users", ()).is_err());
+        assert!(router.insert("/users/x{id}", ()).is_err());
+    }
+
+    #[test]
+    fn test_matches_works_when_priority() {
+        let mut router = Router::new();
+        router.insert("/{*rest}", "wildcard").unwrap();
+        router.insert("/files/{name}", "param").unwrap();
+        router.insert("/files/readme", "static").unwrap();
+        router.insert("/{dir}/readme", "param first").unwrap();
+
+        let value = |input: &str| {
+            *router
+                .matches(&Parser::new(None).parse(input).unwrap())
+                .unwrap()
+                .route
+                .value()
+        };
+        assert_eq!(value("https://example.com/files/readme"), "static");
+        assert_eq!(value("https://example.com/files/other"), "param");
+        assert_eq!(value("https://example.com/docs/readme"), "param first");
+        assert_eq!(value("https://example.com/files/a/b"), "wildcard");
+        assert_eq!(value("https://example.com"), "wildcard");
+    }
+
+    #[test]
+    fn test_matches_works_when_tie_goes_to_first() {
+        let mut router = Router::new();
+        router.insert("/{a}", 1).unwrap();
+        router.insert("/{b}", 2).unwrap();
+        let result = router
+            .matches(&Parser::new(None).parse("https://example.com/x").unwrap())
+            .unwrap();
+        assert_eq!(*result.route.value(), 1);
+        assert_eq!(result.route.template(), "/{a}");
+    }
+
+    #[test]
+    fn test_matches_works_when_encoded_and_trailing_slash() {
+        let mut router = Router::new();
+        router.insert("/users/{id}/", ()).unwrap();
+        let result = router
+            .matches(
+                &Parser::new(None)
+                    .parse("https://example.com/users/J%C3%B6rg/")
+                    .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(result.params["id"], "Jörg");
+        assert!(router
+            .matches(
+                &Parser::new(None)
+                    .parse("https://example.com/users")
+                    .unwrap()
+            )
+            .is_none());
+    }
+
+    #[test]
+    fn test_matches_works_when_dot_segments() {
+        let mut router = Router::new();
+        router.insert("/public/{*rest}", "public").unwrap();
+        router.insert("/admin/{*rest}", "admin").unwrap();
+        let value = |input: &str| {
+            *router
+                .matches(&Parser::new(None).parse(input).unwrap())
+                .unwrap()
+                .route
+                .value()
+        };
+        assert_eq!(value("https://example.com/public/../admin/x"), "admin");
+        assert_eq!(value("https://example.com/public/%2e%2e/admin/x"), "admin");
+        assert_eq!(value("https://example.com/public/%2E./admin/x"), "admin");
+        assert_eq!(value("https://example.com/admin/../public/./x"), "public");
+
+        let result = router
+            .matches(
+                &Parser::new(None)
+                    .parse("https://example.com/public/a/./b/../c")
+                    .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(result.params["rest"], "a/c");
+    }
+
+    #[test]
+    fn test_matches_works_when_encoded_slash_in_wildcard() {
+        let mut router = Router::new();
+        router.insert("/files/{*rest}", ()).unwrap();
+        router.insert("/files/{dir}/{name}", ()).unwrap();
+        let result = router
+            .matches(
+                &Parser::new(None)
+                    .parse("https://example.com/files/a%2F..%2Fb")
+                    .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(result.route.template(), "/files/{*rest}");
+        assert_eq!(result.params["rest"], "a%2F..%2Fb");
+        let result = router
+            .matches(
+                &Parser::new(None)
+                    .parse("https://example.com/files/a%2Fb/c")
+                    .unwrap(),
+            )
+            .unwrap();
+        assert_eq!(result.params["dir"], "a/b");
+    }
+
+    #[test]
+    fn test_matches_is_none_when_constraints_fail() {
+        let mut router = Router::new();
+        let options = RouteOptions {
+            scheme: Some("https".to_string()),
+            hosts: Some(vec!["example.com".to_string()]),
+        };
+        router.insert_with("/", options, ()).unwrap();
+        assert!(router
+            .matches(&Parser::new(None).parse("https://example.com/").unwrap())
+            .is_some());
+        assert!(router
+            .matches(&Parser::new(None).parse("http://example.com/").unwrap())
+            .is_none());
+        assert!(router
+            .matches(&Parser::new(None).parse("https://example.org/").unwrap())
+            .is_none());
+    }
+}
@@ -0,0 +1,419 @@
+use crate::error::ParseError;
+use crate::host_matcher::HostMatcher;
+use crate::url::Url;
+use regex::Regex;
+
+/// A compiled set of rules for removing tracking parameters from the query of a URL.
+///
+/// Rules are grouped in providers, as in [ClearURLs](https://docs.clearurls.xyz/latest/specs/rules/): a provider applies
+/// to the URLs matching its hosts and its URL pattern, except those matching one of its exceptions. Its rules are
+/// regular expressions matched case-insensitively against entire decoded parameter names.
+///
+/// # Example
+/// ```rust
+/// use url_parse::core::Parser;
+/// use url_parse::tracking::{TrackingProvider, TrackingRules};
+/// let mut rules = TrackingRules::builtin();
+/// rules.insert(TrackingProvider {
+///     name: "shop".to_string(),
+///     hosts: Some(vec!["shop.example.com".to_string()]),
+///     rules: vec!["ref".to_string()],
+///     ..TrackingProvider::default()
+/// }).unwrap();
+///
+/// let url = Parser::new(None).parse("https://shop.example.com/item?id=7&utm_source=mail&ref=home&fbclid=x1").unwrap();
+/// let result = rules.clean(&url);
+/// assert_eq!(result.url.serialize(), "https://shop.example.com/item?id=7");
+/// assert_eq!(result.removed_keys(), vec!["utm_source", "ref", "fbclid"]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct TrackingRules {
+    providers: Vec<Provider>,
+}
+
+/// The definition of a group of tracking rules.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TrackingProvider {
+    /// A name for reporting, i.e. `amazon`.
+    pub name: String,
+    /// Host patterns the URL must match, see `HostMatcher`. `None` matches any host.
+    pub hosts: Option<Vec<String>>,
+    /// A regular expression the whole URL must match. `None` matches any URL.
+    pub url_pattern: Option<String>,
+    /// Regular expressions of the parameter names to remove.
+    pub rules: Vec<String>,
+    /// Regular expressions of whole URLs the provider does not apply to.
+    pub exceptions: Vec<String>,
+}
+
+/// The result of cleaning a URL.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Cleaned {
+    /// The URL without the removed parameters, or the input unchanged if none were removed.
+    pub url: Url,
+    /// The removed pairs, decoded and in order, with the name of the provider which removed them.
+    pub removed: Vec<RemovedParam>,
+}
+
+/// A query parameter removed by a `TrackingRules`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RemovedParam {
+    pub key: String,
+    pub value: String,
+    pub provider: String,
+}
+
+#[derive(Debug, Clone)]
+struct Provider {
+    name: String,
+    hosts: Option<HostMatcher>,
+    url_pattern: Option<Regex>,
+    rules: Regex,
+    exceptions: Vec<Regex>,
+}
+
+/// Parameters used for tracking on any site.
+const GLOBAL_RULES: &[&str] = &[
+    r"utm_\w+",
+    "fbclid",
+    "gclid",
+    "gclsrc",
+    "dclid",
+    "gbraid",
+    "wbraid",
+    "msclkid",
+    "yclid",
+    "twclid",
+    "igshid",
+    "mc_cid",
+    "mc_eid",
+    "_hsenc",
+    "_hsmi",
+    "mkt_tok",
+    "oly_anon_id",
+    "oly_enc_id",
+    "vero_id",
+    "wickedid",
+    "_openstat",
+];
+
+/// Hosts, as `HostMatcher` patterns, and parameters of site-specific tracking.
+const SITE_RULES: &[(&str, &[&str], &[&str])] = &[
+    (
+        "amazon",
+        &[
+            "amazon.com",
+            "*.amazon.com",
+            "amazon.co.uk",
+            "*.amazon.co.uk",
+            "amazon.de",
+            "*.amazon.de",
+            "amazon.fr",
+            "*.amazon.fr",
+        ],
+        &[
+            r"pd_rd_\w+",
+            r"pf_rd_\w+",
+            "ref_?",
+            "_encoding",
+            "psc",
+            "content-id",
+            "qid",
+            "sr",
+        ],
+    ),
+    (
+        "youtube",
+        &["youtube.com", "*.youtube.com", "youtu.be"],
+        &["si", "feature", "pp", "kw"],
+    ),
+    (
+        "twitter",
+        &["twitter.com", "*.twitter.com", "x.com", "*.x.com"],
+        &["s", "t", "ref_src", "ref_url", "cn"],
+    ),
+];
+
+impl TrackingRules {
+    /// Create an empty rule set.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Create a rule set with the built-in global and site-specific rules.
+    pub fn builtin() -> Self {
+        let mut rules = Self::new();
+        let global = TrackingProvider {
+            name: "global".to_string(),
+            rules: GLOBAL_RULES.iter().map(|r| r.to_string()).collect(),
+            ..TrackingProvider::default()
+        };
+        rules.insert(global).expect("valid built-in rules");
+        for (name, hosts, params) in SITE_RULES {
+            let provider = TrackingProvider {
+                name: name.to_string(),
+                hosts: Some(hosts.iter().map(|h| h.to_string()).collect()),
+                rules: params.iter().map(|r| r.to_string()).collect(),
+                ..TrackingProvider::default()
+            };
+            rules.insert(provider).expect("valid built-in rules");
+        }
+        rules
+    }
+
+    /// Add a provider. Fails if a host pattern or a regular expression is invalid.
+    pub fn insert(&mut self, provider: TrackingProvider) -> Result<(), ParseError> {
+        let compile =
+            |pattern: &str| Regex::new(&format!("(?i){pattern}")).map_err(|_| ParseError {});
+        let hosts = match &provider.hosts {
+            Some(patterns) => Some(HostMatcher::from_patterns(
+                patterns.iter().map(|p| p.as_str()),
+            )?),
+            None => None,
+        };
+        let url_pattern = match &provider.url_pattern {
+            Some(pattern) => Some(compile(pattern)?),
+            None => None,
+        };
+        let rules = match provider.rules.is_empty() {
+            true => r"[^\s\S]".to_string(),
+            false => format!("^(?:{})$", provider.rules.join("|")),
+        };
+        self.providers.push(Provider {
+            name: provider.name,
+            hosts,
+            url_pattern,
+            rules: compile(&rules)?,
+            exceptions: provider
+                .exceptions
+                .iter()
+                .map(|e| compile(e))
+                .collect::<Result<_, _>>()?,
+        });
+        Ok(())
+    }
+
+    /// Load providers from a ClearURLs rules document. Each provider keeps its `urlPattern`, `rules`,
+    /// `referralMarketing` and `exceptions`; other fields are ignored.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// use url_parse::tracking::TrackingRules;
+    /// let input = r#"{ "providers": { "example": {
+    ///     "urlPattern": "^https?://(?:[a-z0-9-]+\\.)*?example\\.com",
+    ///     "rules": ["ref", "src_[a-z]+"],
+    ///     "exceptions": ["^https?://example\\.com/keep"]
+    /// } } }"#;
+    /// let rules = TrackingRules::from_clearurls_json(input).unwrap();
+    /// let parser = Parser::new(None);
+    /// let url = parser.parse("https://www.example.com/a?ref=x&SRC_MAIL=1&id=2").unwrap();
+    /// assert_eq!(rules.clean(&url).url.query, Some("id=2".to_string()));
+    /// let url = parser.parse("https://example.com/keep?ref=x").unwrap();
+    /// assert!(rules.clean(&url).removed.is_empty());
+    /// ```
+    #[cfg(feature = "json")]
+    pub fn from_clearurls_json(input: &str) -> Result<Self, ParseError> {
+        let document: serde_json::Value = serde_json::from_str(input).map_err(|_| ParseError {})?;
+        let providers = document
+            .get("providers")
+            .and_then(|p| p.as_object())
+            .ok_or(ParseError {})?;
+        let strings = |value: &serde_json::Value, field: &str| -> Vec<String> {
+            value
+                .get(field)
+                .and_then(|v| v.as_array())
+                .into_iter()
+                .flatten()
+                .filter_map(|v| v.as_str())
+                .map(|v| v.to_string())
+                .collect()
+        };
+        let mut rules = Self::new();
+        for (name, value) in providers {
+            let url_pattern = value.get("urlPattern").and_then(|v| v.as_str());
+            let mut params = strings(value, "rules");
+            params.extend(strings(value, "referralMarketing"));
+            rules.insert(TrackingProvider {
+                name: name.to_string(),
+                hosts: None,
+                url_pattern: url_pattern.map(|v| v.to_string()),
+                rules: params,
+                exceptions: strings(value, "exceptions"),
+            })?;
+        }
+        Ok(rules)
+    }
+
+    /// Remove the tracking parameters from the query of the URL. Other pairs are kept as written, and the URL is
+    /// returned untouched if nothing is removed.
+    pub fn clean(&self, url: &Url) -> Cleaned {
+        let address = url.serialize_unredacted();
+        let providers: Vec<&Provider> = self
+            .providers
+            .iter()
+            .filter(|provider| provider.applies(url, &address))
+            .collect();
+        let mut removed = vec![];
+        let mut result = url.clone();
+        result.retain(|key, value| {
+            match providers
+                .iter()
+                .find(|provider| provider.rules.is_match(key))
+            {
+                Some(provider) => {
+                    removed.push(RemovedParam {
+                        key: key.to_string(),
+                        value: value.to_string(),
+                        provider: provider.name.to_string(),
+                    });
+                    false
+                }
+                None => true,
+            }
+        });
+        let url = match removed.is_empty() {
+            true => url.clone(),
+            false => result,
+        };
+        Cleaned { url, removed }
+    }
+}
+
+impl Provider {
+    fn applies(&self, url: &Url, address: &str) -> bool {
+        self.hosts.as_ref().is_none_or(|h| h.matches(url))
+            && self
+                .url_pattern
+                .as_ref()
+                .is_none_or(|p| p.is_match(address))
+            && !self.exceptions.iter().any(|e| e.is_match(address))
+    }
+}
+
+impl Cleaned {
+    /// The keys of the removed parameters, in order.
+    pub fn removed_keys(&self) -> Vec<&str> {
+        self.removed.iter().map(|p| p.key.as_str()).collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Parser;
+
+    #[test]
+    fn test_clean_works_when_builtin_global() {
+        let url = Parser::new(None)
+            .parse("https://news.example.org/a?UTM_Source=x&utm_campaign=y&gclid=1&page=2#top")
+            .unwrap();
+        let result = TrackingRules::builtin().clean(&url);
+        assert_eq!(
+            result.url.serialize(),
+            "https://news.example.org/a?page=2#top"
+        );
+        assert_eq!(
+            result.removed[0],
+            RemovedParam {
+                key: "UTM_Source".to_string(),
+                value: "x".to_string(),
+                provider: "global".to_string(),
+            }
+        );
+        assert_eq!(result.removed.len(), 3);
+    }
+
+    #[test]
+    fn test_clean_works_when_per_host() {
+        let rules = TrackingRules::builtin();
+        let url = Parser::new(None)
+            .parse("https://www.youtube.com/watch?v=abc&si=tok&feature=share")
+            .unwrap();
+        let result = rules.clean(&url);
+        assert_eq!(result.url.query, Some("v=abc".to_string()));
+        assert_eq!(result.removed_keys(), vec!["si", "feature"]);
+
+        let url = Parser::new(None)
+            .parse("https://example.com/watch?v=abc&si=tok")
+            .unwrap();
+        assert!(rules.clean(&url).removed.is_empty());
+    }
+
+    #[test]
+    fn test_clean_works_when_all_removed() {
+        let url = Parser::new(None)
+            .parse("https://example.com/?fbclid=1&utm_medium=x")
+            .unwrap();
+        let result = TrackingRules::builtin().clean(&url);
+        assert_eq!(result.url.query, None);
+        assert_eq!(result.url.serialize(), "https://example.com/");
+    }
+
+    #[test]
+    fn test_clean_is_unchanged_when_nothing_removed() {
+        let rules = TrackingRules::builtin();
+        for input in [
+            "https://example.com/?",
+            "https://example.com/?a=1&&b=2",
+            "https://example.com/?q=a%20b+c&flag",
+        ] {
+            let url = Parser::new(None).parse(input).unwrap();
+            let result = rules.clean(&url);
+            assert_eq!(result.url, url);
+            assert_eq!(result.url.serialize(), input);
+            assert!(result.removed.is_empty());
+        }
+    }
+
+    #[test]
+    fn test_clean_works_when_exception() {
+        let mut rules = TrackingRules::new();
+        rules
+            .insert(TrackingProvider {
+                name: "custom".to_string(),
+                url_pattern: Some(r"^https://example\.com/".to_string()),
+                rules: vec!["ref".to_string()],
+                exceptions: vec![r"/login\b".to_string()],
+                ..TrackingProvider::default()
+            })
+            .unwrap();
+        let result = rules.clean(
+            &Parser::new(None)
+                .parse("https://example.com/a?ref=1&refx=2")
+                .unwrap(),
+        );
+        assert_eq!(result.url.query, Some("refx=2".to_string()));
+        let result = rules.clean(
+            &Parser::new(None)
+                .parse("https://example.com/login?ref=1")
+                .unwrap(),
+        );
+        assert!(result.removed.is_empty());
+        let result = rules.clean(
+            &Parser::new(None)
+                .parse("https://example.org/a?ref=1")
+                .unwrap(),
+        );
+        assert!(result.removed.is_empty());
+    }
+
+    #[test]
+    fn test_insert_fails_when_invalid_rule() {
+        let mut rules = TrackingRules::new();
+        let provider = TrackingProvider {
+            rules: vec!["(".to_string()],
+            ..TrackingProvider::default()
+        };
+        assert!(rules.insert(provider).is_err());
+    }
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn test_from_clearurls_json_fails_when_invalid() {
+        assert!(TrackingRules::from_clearurls_json("{}").is_err());
+        assert!(TrackingRules::from_clearurls_json("[").is_err());
+        let input = r#"{ "providers": { "x": { "urlPattern": "(" } } }"#;
+        assert!(TrackingRules::from_clearurls_json(input).is_err());
+    }
+}
@@ -0,0 +1,225 @@
+use crate::core::Parser;
+use crate::encoding::{decode_utf8, encode, EncodeSet};
+use crate::error::ParseError;
+use crate::url::Url;
+use std::path::{Path, PathBuf};
+
+/// The flavour of file system path to convert from or to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PathStyle {
+    /// `/home/user/file.txt`
+    Unix,
+    /// `C:\Users\file.txt` or `\\server\share\file.txt`
+    Windows,
+}
+
+impl PathStyle {
+    /// The path style of the platform this crate is compiled for.
+    pub fn native() -> Self {
+        match cfg!(windows) {
+            true => PathStyle::Windows,
+            false => PathStyle::Unix,
+        }
+    }
+}
+
+impl Url {
+    /// Create a `file:` URL from an absolute path of the current platform.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(unix)] {
+    /// use std::path::Path;
+    /// use url_parse::url::Url;
+    /// let url = Url::from_file_path(Path::new("/tmp/my file.txt")).unwrap();
+    /// assert_eq!(url.serialize(), "file:///tmp/my%20file.txt");
+    /// # }
+    /// ```
+    pub fn from_file_path(path: &Path) -> Result<Url, ParseError> {
+        let path = path.to_str().ok_or(ParseError {})?;
+        Self::from_file_path_str(path, PathStyle::native())
+    }
+
+    /// Convert a `file:` URL to a path of the current platform.
+    ///
+    /// # Example
+    /// ```rust
+    /// # #[cfg(unix)] {
+    /// use std::path::PathBuf;
+    /// use url_parse::core::Parser;
+    /// let url = Parser::new(None).parse("file:///tmp/my%20file.txt").unwrap();
+    /// assert_eq!(url.to_file_path().unwrap(), PathBuf::from("/tmp/my file.txt"));
+    /// # }
+    /// ```
+    pub fn to_file_path(&self) -> Result<PathBuf, ParseError> {
+        self.to_file_path_str(PathStyle::native())
+            .map(PathBuf::from)
+    }
+
+    /// Create a `file:` URL from an absolute path written in the given style.
+    /// Segments are percent-encoded, Windows drive letters become the first segment and
+    /// UNC paths (`\\server\share`) become the host.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::url::{PathStyle, Url};
+    /// let url = Url::from_file_path_str(r"C:\Users\Zoë\notes #1.txt", PathStyle::Windows).unwrap();
+    /// assert_eq!(url.serialize(), "file:///C:/Users/Zo%C3%AB/notes%20%231.txt");
+    /// let url = Url::from_file_path_str(r"\\server\share\file.txt", PathStyle::Windows).unwrap();
+    /// assert_eq!(url.serialize(), "file://server/share/file.txt");
+    /// ```
+    pub fn from_file_path_str(path: &str, style: PathStyle) -> Result<Url, ParseError> {
+        let (host, segments): (&str, Vec<&str>) = match style {
+            PathStyle::Unix => {
+                let rest = path.strip_prefix('/').ok_or(ParseError {})?;
+                ("", rest.split('/').collect())
+            }
+            PathStyle::Windows => match path.strip_prefix(r"\\") {
+                Some(unc) => {
+                    let (host, rest) = unc.split_once(['\\', '/']).ok_or(ParseError {})?;
+                    if host.is_empty() || host == "?" || host == "." {
+                        return Err(ParseError {});
+                    }
+                    (host, rest.split(['\\', '/']).collect())
+                }
+                None => {
+                    if !Self::is_drive(path.get(..2).unwrap_or_default())
+                        || !path[2..].starts_with(['\\', '/'])
+                    {
+                        return Err(ParseError {});
+                    }
+                    ("", path.split(['\\', '/']).collect())
+                }
+            },
+        };
+        let path = segments
+            .iter()
+            .map(|segment| encode(segment, EncodeSet::PathSegment))
+            .collect::<Vec<String>>()
+            .join("/");
+        Parser::new(None).parse(&format!("file://{host}/{path}"))
+    }
+
+    /// Convert a `file:` URL to a path written in the given style.
+    /// Fails if the scheme is not `file`, a segment decodes to a separator or invalid UTF-8,
+    /// or the URL cannot be expressed in the style (i.e. a remote host on Unix).
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// use url_parse::url::PathStyle;
+    /// let url = Parser::new(None).parse("file:///C:/Program%20Files/app.exe").unwrap();
+    /// assert_eq!(url.to_file_path_str(PathStyle::Windows).unwrap(), r"C:\Program Files\app.exe");
+    /// let url = Parser::new(None).parse("file://server/share/file.txt").unwrap();
+    /// assert_eq!(url.to_file_path_str(PathStyle::Windows).unwrap(), r"\\server\share\file.txt");
+    /// assert!(url.to_file_path_str(PathStyle::Unix).is_err());
+    /// ```
+    pub fn to_file_path_str(&self, style: PathStyle) -> Result<String, ParseError> {
+        if !self
+            .scheme
+            .as_deref()
+            .is_some_and(|s| s.eq_ignore_ascii_case("file"))
+        {
+            return Err(ParseError {});
+        }
+        let host = self
+            .hostname()
+            .filter(|host| !host.eq_ignore_ascii_case("localhost"));
+        let separator = match style {
+            PathStyle::Unix => '/',
+            PathStyle::Windows => '\\',
+        };
+        let mut segments = vec![];
+        for segment in self.path.iter().flatten() {
+            let segment = decode_utf8(segment)?;
+            if segment.contains(['/', '\0', separator]) {
+                return Err(ParseError {});
+            }
+            segments.push(segment);
+        }
+        let path = segments.join(&separator.to_string());
+
+        match (style, host) {
+            (PathStyle::Unix, None) => Ok(format!("/{path}")),
+            (PathStyle::Unix, Some(_)) => Err(ParseError {}),
+            (PathStyle::Windows, Some(host)) => Ok(format!(r"\\{host}\{path}")),
+            (PathStyle::Windows, None) => match segments.first() {
+                Some(drive) if Self::is_drive(drive) => {
+                    let path = &path[2..];
+                    match path.is_empty() {
+                        true => Ok(format!(r"{}:\", &drive[..1])),
+                        false => Ok(format!("{}:{path}", &drive[..1])),
+                    }
+                }
+                _ => Err(ParseError {}),
+            },
+        }
+    }
+
+    /// Check for a Windows drive letter, also accepting the legacy `C|` form.
+    fn is_drive(segment: &str) -> bool {
+        let bytes = segment.as_bytes();
+        bytes.len() == 2 && bytes[0].is_ascii_alphabetic() && matches!(bytes[1], b':' | b'|')
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_from_file_path_str_works_when_unix() {
+        let url = Url::from_file_path_str("/home/user/a?b%c/", PathStyle::Unix).unwrap();
+        assert_eq!(url.scheme, Some("file".to_string()));
+        assert_eq!(url.hostname(), None);
+        assert_eq!(url.serialize(), "file:///home/user/a%3Fb%25c/");
+    }
+
+    #[test]
+    fn test_from_file_path_str_fails_when_relative() {
+        assert!(Url::from_file_path_str("relative/path", PathStyle::Unix).is_err());
+        assert!(Url::from_file_path_str(r"relative\path", PathStyle::Windows).is_err());
+        assert!(Url::from_file_path_str("C:relative", PathStyle::Windows).is_err());
+        assert!(Url::from_file_path_str(r"\\?\C:\verbatim", PathStyle::Windows).is_err());
+    }
+
+    #[test]
+    fn test_to_file_path_str_works_when_roundtrip() {
+        let inputs = [
+            ("/", PathStyle::Unix),
+            ("/tmp/ünïcødé dir/file #1.txt", PathStyle::Unix),
+            (r"C:\", PathStyle::Windows),
+            (r"D:\data\100% real.csv", PathStyle::Windows),
+            (r"\\fs.corp.example.com\share\dir\", PathStyle::Windows),
+        ];
+        for (input, style) in inputs {
+            let url = Url::from_file_path_str(input, style).unwrap();
+            assert_eq!(url.to_file_path_str(style).unwrap(), input);
+        }
+    }
+
+    #[test]
+    fn test_to_file_path_str_works_when_localhost_and_legacy_drive() {
+        let url = Parser::new(None).parse("file://localhost/C|/x").unwrap();
+        assert_eq!(url.to_file_path_str(PathStyle::Windows).unwrap(), r"C:\x");
+        let url = Parser::new(None)
+            .parse("file://localhost/etc/hosts")
+            .unwrap();
+        assert_eq!(url.to_file_path_str(PathStyle::Unix).unwrap(), "/etc/hosts");
+    }
+
+    #[test]
+    fn test_to_file_path_str_fails_when_invalid() {
+        let parser = Parser::new(None);
+        let url = parser.parse("https://example.com/a").unwrap();
+        assert!(url.to_file_path_str(PathStyle::Unix).is_err());
+        let url = parser.parse("file:///a%2Fb").unwrap();
+        assert!(url.to_file_path_str(PathStyle::Unix).is_err());
+        let url = parser.parse("file:///a%5Cb").unwrap();
+        assert!(url.to_file_path_str(PathStyle::Windows).is_err());
+        let url = parser.parse("file:///a/%FF").unwrap();
+        assert!(url.to_file_path_str(PathStyle::Unix).is_err());
+        let url = parser.parse("file:///no/drive").unwrap();
+        assert!(url.to_file_path_str(PathStyle::Windows).is_err());
+    }
+}
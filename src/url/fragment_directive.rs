@@ -0,0 +1,281 @@
+use crate::encoding::{decode_utf8_lossy, encode, EncodeSet};
+use crate::url::Url;
+
+/// The delimiter between the regular fragment and the fragment directive.
+const DELIMITER: &str = ":~:";
+
+/// The part of the anchor after `:~:`, made of `&`-separated directives.
+/// Text directives are parsed; other and invalid directives are kept as written.
+///
+/// # Example
+/// ```rust
+/// use url_parse::url::FragmentDirective;
+/// let directive = FragmentDirective::parse("text=an%20example,-text&text=other&note=x");
+/// assert_eq!(directive.text[0].start, "an example");
+/// assert_eq!(directive.text[0].suffix, Some("text".to_string()));
+/// assert_eq!(directive.text[1].start, "other");
+/// assert_eq!(directive.other, vec!["note=x"]);
+/// assert_eq!(directive.to_string(), "text=an%20example,-text&text=other&note=x");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FragmentDirective {
+    pub text: Vec<TextDirective>,
+    pub other: Vec<String>,
+}
+
+/// A `text=[prefix-,]start[,end][,-suffix]` directive, percent-decoded.
+///
+/// # Example
+/// ```rust
+/// use url_parse::url::TextDirective;
+/// let directive = TextDirective::parse("the-,quick%2Dbrown,lazy%20dog,-barks").unwrap();
+/// assert_eq!(directive.prefix, Some("the".to_string()));
+/// assert_eq!(directive.start, "quick-brown");
+/// assert_eq!(directive.end, Some("lazy dog".to_string()));
+/// assert_eq!(directive.suffix, Some("barks".to_string()));
+/// assert_eq!(directive.to_string(), "text=the-,quick%2Dbrown,lazy%20dog,-barks");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TextDirective {
+    pub prefix: Option<String>,
+    pub start: String,
+    pub end: Option<String>,
+    pub suffix: Option<String>,
+}
+
+impl FragmentDirective {
+    /// Split a raw fragment directive, without the leading `:~:`, at its `&` separators.
+    pub fn parse(directive: &str) -> Self {
+        let mut result = Self::default();
+        for part in directive.split('&').filter(|part| !part.is_empty()) {
+            match part.strip_prefix("text=").and_then(TextDirective::parse) {
+                Some(text) => result.text.push(text),
+                None => result.other.push(part.to_string()),
+            }
+        }
+        result
+    }
+
+    /// Check whether there are no directives.
+    pub fn is_empty(&self) -> bool {
+        self.text.is_empty() && self.other.is_empty()
+    }
+}
+
+impl std::fmt::Display for FragmentDirective {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let parts: Vec<String> = self
+            .text
+            .iter()
+            .map(|text| text.to_string())
+            .chain(self.other.iter().cloned())
+            .collect();
+        write!(f, "{}", parts.join("&"))
+    }
+}
+
+impl TextDirective {
+    /// Create a directive matching the given text.
+    pub fn new(start: &str) -> Self {
+        Self {
+            start: start.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Parse the value of a text directive, without `text=`. `None` if the start is missing, a part is empty
+    /// or there are too many parts.
+    pub fn parse(value: &str) -> Option<Self> {
+        let mut parts: Vec<&str> = value.split(',').collect();
+        if parts.len() > 4 || parts.iter().any(|part| part.is_empty()) {
+            return None;
+        }
+        let prefix = parts[0].strip_suffix('-');
+        if prefix.is_some() {
+            parts.remove(0);
+        }
+        let suffix = parts.last().and_then(|part| part.strip_prefix('-'));
+        if suffix.is_some() {
+            parts.pop();
+        }
+        let (start, end) = match parts[..] {
+            [start] => (start, None),
+            [start, end] => (start, Some(end)),
+            _ => return None,
+        };
+        if [prefix, suffix]
+            .iter()
+            .flatten()
+            .any(|part| part.is_empty())
+        {
+            return None;
+        }
+        Some(Self {
+            prefix: prefix.map(decode_utf8_lossy),
+            start: decode_utf8_lossy(start),
+            end: end.map(decode_utf8_lossy),
+            suffix: suffix.map(decode_utf8_lossy),
+        })
+    }
+}
+
+/// Serialize as `text=...`, escaping the `-`, `,` and `&` delimiters within the texts.
+impl std::fmt::Display for TextDirective {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let escape = |text: &str| encode(text, EncodeSet::Component).replace('-', "%2D");
+        write!(f, "text=")?;
+        if let Some(prefix) = &self.prefix {
+            write!(f, "{}-,", escape(prefix))?;
+        }
+        write!(f, "{}", escape(&self.start))?;
+        if let Some(end) = &self.end {
+            write!(f, ",{}", escape(end))?;
+        }
+        if let Some(suffix) = &self.suffix {
+            write!(f, ",-{}", escape(suffix))?;
+        }
+        Ok(())
+    }
+}
+
+impl Url {
+    /// Extract the anchor without its fragment directive. `None` if nothing precedes the directive.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// let url = Parser::new(None).parse("https://example.com/page#section:~:text=start,end").unwrap();
+    /// assert_eq!(url.anchor_without_directive(), Some("section"));
+    /// let url = Parser::new(None).parse("https://example.com/page#:~:text=start").unwrap();
+    /// assert_eq!(url.anchor_without_directive(), None);
+    /// ```
+    pub fn anchor_without_directive(&self) -> Option<&str> {
+        let anchor = self.anchor.as_deref()?;
+        let fragment = anchor
+            .split_once(DELIMITER)
+            .map_or(anchor, |(fragment, _)| fragment);
+        match fragment.is_empty() && anchor.contains(DELIMITER) {
+            true => None,
+            false => Some(fragment),
+        }
+    }
+
+    /// Extract the fragment directive of the anchor. `None` if the anchor has no `:~:`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// let url = Parser::new(None).parse("https://example.com/page#section:~:text=an-,example").unwrap();
+    /// let result = url.fragment_directive().unwrap();
+    /// assert_eq!(result.text[0].prefix, Some("an".to_string()));
+    /// assert_eq!(result.text[0].start, "example");
+    /// ```
+    pub fn fragment_directive(&self) -> Option<FragmentDirective> {
+        let (_, directive) = self.anchor.as_deref()?.split_once(DELIMITER)?;
+        Some(FragmentDirective::parse(directive))
+    }
+
+    /// Replace the fragment directive, keeping the regular fragment. `None` or an empty directive removes it.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// use url_parse::url::{FragmentDirective, TextDirective};
+    /// let mut url = Parser::new(None).parse("https://example.com/page#section").unwrap();
+    /// let directive = FragmentDirective {
+    ///     text: vec![TextDirective { end: Some("end, really".to_string()), ..TextDirective::new("start") }],
+    ///     other: vec![],
+    /// };
+    /// url.set_fragment_directive(Some(&directive));
+    /// assert_eq!(url.serialize(), "https://example.com/page#section:~:text=start,end%2C%20really");
+    /// url.set_fragment_directive(None);
+    /// assert_eq!(url.serialize(), "https://example.com/page#section");
+    /// ```
+    pub fn set_fragment_directive(&mut self, directive: Option<&FragmentDirective>) {
+        let fragment = self.anchor_without_directive().map(|f| f.to_string());
+        self.anchor = match directive.filter(|d| !d.is_empty()) {
+            Some(directive) => Some(format!(
+                "{}{DELIMITER}{directive}",
+                fragment.unwrap_or_default()
+            )),
+            None => fragment,
+        };
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Parser;
+
+    #[test]
+    fn test_text_directive_parse_works_when_prefix_or_suffix_only() {
+        let result = TextDirective::parse("a-,b").unwrap();
+        assert_eq!(
+            (result.prefix.as_deref(), result.start.as_str()),
+            (Some("a"), "b")
+        );
+        let result = TextDirective::parse("b,-c").unwrap();
+        assert_eq!(
+            (result.start.as_str(), result.suffix.as_deref()),
+            ("b", Some("c"))
+        );
+        let result = TextDirective::parse("a-,b,c,-d").unwrap();
+        assert_eq!(result.end, Some("c".to_string()));
+    }
+
+    #[test]
+    fn test_text_directive_parse_is_none_when_invalid() {
+        for input in [
+            "",
+            "a-",
+            "-b",
+            "a-,-b",
+            "-,b",
+            "b,-",
+            "a,",
+            ",a",
+            "a,b,c",
+            "a-,b,c,d,-e",
+        ] {
+            assert_eq!(TextDirective::parse(input), None, "{input}");
+        }
+        let result = FragmentDirective::parse("text=a,b,c&&text=ok");
+        assert_eq!(result.other, vec!["text=a,b,c"]);
+        assert_eq!(result.text, vec![TextDirective::new("ok")]);
+    }
+
+    #[test]
+    fn test_text_directive_works_when_roundtrip() {
+        let directive = TextDirective {
+            prefix: Some("a-b".to_string()),
+            start: "x,&y".to_string(),
+            end: None,
+            suffix: Some("ü".to_string()),
+        };
+        let text = directive.to_string();
+        assert_eq!(text, "text=a%2Db-,x%2C%26y,-%C3%BC");
+        let value = text.strip_prefix("text=").unwrap();
+        assert_eq!(TextDirective::parse(value), Some(directive));
+    }
+
+    #[test]
+    fn test_fragment_directive_is_none_when_no_delimiter() {
+        let url = Parser::new(None)
+            .parse("https://example.com/page#section")
+            .unwrap();
+        assert_eq!(url.fragment_directive(), None);
+        assert_eq!(url.anchor_without_directive(), Some("section"));
+        assert_eq!(Url::empty().anchor_without_directive(), None);
+    }
+
+    #[test]
+    fn test_set_fragment_directive_works_when_no_anchor() {
+        let mut url = Parser::new(None).parse("https://example.com/page").unwrap();
+        let directive = FragmentDirective::parse("text=hello");
+        url.set_fragment_directive(Some(&directive));
+        assert_eq!(url.anchor, Some(":~:text=hello".to_string()));
+        url.set_fragment_directive(Some(&FragmentDirective::default()));
+        assert_eq!(url.anchor, None);
+    }
+}
@@ -0,0 +1,143 @@
+use crate::encoding::decode_utf8_lossy;
+use crate::url::Url;
+
+/// A path segment split into its name and its `;key=value` parameters, as written (i.e. percent-encoded).
+///
+/// # Example
+/// ```rust
+/// use url_parse::url::MatrixSegment;
+/// let segment = MatrixSegment::parse("cars;color=red;year=2012;used");
+/// assert_eq!(segment.name, "cars");
+/// assert_eq!(segment.param("year"), Some("2012".to_string()));
+/// assert_eq!(segment.params[2], ("used".to_string(), None));
+/// assert_eq!(segment.to_string(), "cars;color=red;year=2012;used");
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct MatrixSegment {
+    pub name: String,
+    pub params: Vec<(String, Option<String>)>,
+}
+
+impl MatrixSegment {
+    /// Split a raw path segment at its `;` separators.
+    pub fn parse(segment: &str) -> Self {
+        let mut parts = segment.split(';');
+        let name = parts.next().unwrap_or_default().to_string();
+        let params = parts
+            .map(|param| match param.split_once('=') {
+                Some((key, value)) => (key.to_string(), Some(value.to_string())),
+                None => (param.to_string(), None),
+            })
+            .collect();
+        Self { name, params }
+    }
+
+    /// Get the percent-decoded value of the first parameter with the given key.
+    /// A parameter without value yields an empty string.
+    pub fn param(&self, key: &str) -> Option<String> {
+        self.params
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, value)| decode_utf8_lossy(value.as_deref().unwrap_or_default()))
+    }
+}
+
+impl std::fmt::Display for MatrixSegment {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}", self.name)?;
+        for (key, value) in &self.params {
+            match value {
+                Some(value) => write!(f, ";{key}={value}")?,
+                None => write!(f, ";{key}")?,
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Url {
+    /// Extract the path segments together with their matrix parameters.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// let url = Parser::new(None).parse("https://example.com/cars;color=red/models").unwrap();
+    /// let result = url.matrix_segments().unwrap();
+    /// assert_eq!(result[0].param("color"), Some("red".to_string()));
+    /// assert_eq!(result[1].name, "models");
+    /// assert_eq!(url.path_segments().unwrap(), vec!["cars;color=red", "models"]);
+    /// ```
+    pub fn matrix_segments(&self) -> Option<Vec<MatrixSegment>> {
+        self.path
+            .as_ref()
+            .map(|path| path.iter().map(|s| MatrixSegment::parse(s)).collect())
+    }
+
+    /// Replace the path with the given segments and their matrix parameters.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// let mut url = Parser::new(None).parse("ftp://ftp.example.com/pub/file.bin").unwrap();
+    /// let mut segments = url.matrix_segments().unwrap();
+    /// segments[1].params.push(("type".to_string(), Some("i".to_string())));
+    /// url.set_matrix_segments(&segments);
+    /// assert_eq!(url.serialize(), "ftp://ftp.example.com/pub/file.bin;type=i");
+    /// ```
+    pub fn set_matrix_segments(&mut self, segments: &[MatrixSegment]) {
+        self.path = Some(segments.iter().map(|s| s.to_string()).collect());
+    }
+
+    /// Get the percent-decoded value of a matrix parameter of the last path segment (i.e. `;type=i` of ftp URLs).
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// let url = Parser::new(None).parse("ftp://ftp.example.com/pub/file.bin;type=i").unwrap();
+    /// assert_eq!(url.matrix_param("type"), Some("i".to_string()));
+    /// ```
+    pub fn matrix_param(&self, key: &str) -> Option<String> {
+        self.matrix_segments()?.last()?.param(key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Parser;
+
+    #[test]
+    fn test_matrix_segment_parse_works_when_no_params() {
+        let result = MatrixSegment::parse("models");
+        assert_eq!(result.name, "models");
+        assert!(result.params.is_empty());
+        assert_eq!(result.to_string(), "models");
+    }
+
+    #[test]
+    fn test_matrix_segment_parse_works_when_empty_values() {
+        let result = MatrixSegment::parse(";a=;b;c=x%20y=z");
+        assert_eq!(result.name, "");
+        assert_eq!(result.param("a"), Some("".to_string()));
+        assert_eq!(result.param("b"), Some("".to_string()));
+        assert_eq!(result.param("c"), Some("x y=z".to_string()));
+        assert_eq!(result.param("d"), None);
+        assert_eq!(result.to_string(), ";a=;b;c=x%20y=z");
+    }
+
+    #[test]
+    fn test_set_matrix_segments_works_when_roundtrip() {
+        let input = "https://example.com/cars;color=red;year=2012/models;v=2/?q=1";
+        let mut url = Parser::new(None).parse(input).unwrap();
+        let segments = url.matrix_segments().unwrap();
+        assert_eq!(segments.len(), 3);
+        url.set_matrix_segments(&segments);
+        assert_eq!(url.serialize(), input);
+    }
+
+    #[test]
+    fn test_matrix_param_is_none_when_no_path() {
+        let url = Parser::new(None).parse("https://example.com").unwrap();
+        assert_eq!(url.matrix_param("type"), None);
+    }
+}
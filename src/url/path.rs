@@ -0,0 +1,356 @@
+use crate::encoding::{decode_utf8_lossy, encode, EncodeSet};
+use crate::url::Url;
+
+/// Remove the `.` and `..` segments of a path as described in RFC 3986 section 5.2.4.
+/// Percent-encoded dots (`%2e`, `%2E`) are treated as dots, following the WHATWG URL standard.
+///
+/// # Example
+/// ```rust
+/// use url_parse::url::remove_dot_segments;
+/// assert_eq!(remove_dot_segments("/a/b/c/./../../g"), "/a/g");
+/// assert_eq!(remove_dot_segments("mid/content=5/../6"), "mid/6");
+/// assert_eq!(remove_dot_segments("/a/%2e%2E/b/%2e"), "/b/");
+/// ```
+pub fn remove_dot_segments(path: &str) -> String {
+    let (prefix, rest) = match path.strip_prefix('/') {
+        Some(rest) => ("/", rest),
+        None => ("", path),
+    };
+    let segments: Vec<&str> = rest.split('/').collect();
+    format!("{prefix}{}", normalize_segments(&segments).join("/"))
+}
+
+/// Apply dot-segment removal to a list of path segments.
+pub(crate) fn normalize_segments<S: AsRef<str>>(segments: &[S]) -> Vec<String> {
+    let mut output: Vec<String> = vec![];
+    for (i, segment) in segments.iter().enumerate() {
+        let segment = segment.as_ref();
+        let last = i + 1 == segments.len();
+        if is_single_dot(segment) || is_double_dot(segment) {
+            if is_double_dot(segment) {
+                output.pop();
+            }
+            if last {
+                output.push(String::new());
+            }
+            continue;
+        }
+        output.push(segment.to_string());
+    }
+    output
+}
+
+fn is_single_dot(segment: &str) -> bool {
+    segment == "." || segment.eq_ignore_ascii_case("%2e")
+}
+
+fn is_double_dot(segment: &str) -> bool {
+    matches!(
+        segment.to_ascii_lowercase().as_str(),
+        ".." | ".%2e" | "%2e." | "%2e%2e"
+    )
+}
+
+impl Url {
+    /// Remove the `.` and `..` segments from the path of this URL. See `remove_dot_segments()`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// let mut url = Parser::new(None).parse("https://example.com/a/b/../c/./d").unwrap();
+    /// url.normalize_path();
+    /// assert_eq!(url.path_segments().unwrap(), vec!["a", "c", "d"]);
+    /// ```
+    pub fn normalize_path(&mut self) {
+        if let Some(path) = &self.path {
+            self.path = Some(normalize_segments(path));
+        }
+    }
+
+    /// Append a segment to the path. The segment is percent-encoded, so a `/` in it does not start a new segment.
+    /// A trailing slash is replaced by the new segment.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// let mut url = Parser::new(None).parse("https://example.com/docs/").unwrap();
+    /// url.push_segment("a b/c");
+    /// assert_eq!(url.path_segments().unwrap(), vec!["docs", "a%20b%2Fc"]);
+    /// ```
+    pub fn push_segment(&mut self, segment: &str) {
+        let path = self.path.get_or_insert_with(Vec::new);
+        if path.last().is_some_and(|last| last.is_empty()) {
+            path.pop();
+        }
+        path.push(encode(segment, EncodeSet::PathSegment));
+    }
+
+    /// Append several segments to the path. See `push_segment()`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// let mut url = Parser::new(None).parse("https://example.com").unwrap();
+    /// url.extend_segments(["api", "v1", "users"]);
+    /// assert_eq!(url.serialize(), "https://example.com/api/v1/users");
+    /// ```
+    pub fn extend_segments<I, S>(&mut self, segments: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        for segment in segments {
+            self.push_segment(segment.as_ref());
+        }
+    }
+
+    /// Remove the last segment of the path and return it percent-decoded. A trailing slash is kept.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// let mut url = Parser::new(None).parse("https://example.com/a/b%20c/").unwrap();
+    /// assert_eq!(url.pop_segment(), Some("b c".to_string()));
+    /// assert_eq!(url.path_segments().unwrap(), vec!["a", ""]);
+    /// ```
+    pub fn pop_segment(&mut self) -> Option<String> {
+        let path = self.path.as_mut()?;
+        let trailing_slash = path.len() > 1 && path.last().is_some_and(|last| last.is_empty());
+        if trailing_slash {
+            path.pop();
+        }
+        let popped = path.pop().filter(|segment| !segment.is_empty());
+        if trailing_slash || path.is_empty() {
+            path.push(String::new());
+        }
+        popped.map(|segment| decode_utf8_lossy(&segment))
+    }
+
+    /// Extract the percent-decoded last segment of the path. `None` if the path ends with a slash.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// let url = Parser::new(None).parse("https://example.com/files/report%202024.tar.gz").unwrap();
+    /// assert_eq!(url.file_name(), Some("report 2024.tar.gz".to_string()));
+    /// ```
+    pub fn file_name(&self) -> Option<String> {
+        self.path
+            .as_ref()?
+            .last()
+            .filter(|segment| !segment.is_empty())
+            .map(|segment| decode_utf8_lossy(segment))
+    }
+
+    /// Replace the last segment of the path, or fill the place after a trailing slash.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// let mut url = Parser::new(None).parse("https://example.com/files/old.txt").unwrap();
+    /// url.set_file_name("new?.txt");
+    /// assert_eq!(url.serialize(), "https://example.com/files/new%3F.txt");
+    /// ```
+    pub fn set_file_name(&mut self, name: &str) {
+        let path = self.path.get_or_insert_with(Vec::new);
+        path.pop();
+        path.push(encode(name, EncodeSet::PathSegment));
+    }
+
+    /// Extract the file name without its extension. A leading dot does not start an extension.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// let url = Parser::new(None).parse("https://example.com/files/archive.tar.gz").unwrap();
+    /// assert_eq!(url.file_stem(), Some("archive.tar".to_string()));
+    /// ```
+    pub fn file_stem(&self) -> Option<String> {
+        let name = self.file_name()?;
+        match Self::split_extension(&name) {
+            Some((stem, _)) => Some(stem.to_string()),
+            None => Some(name),
+        }
+    }
+
+    /// Extract the extension of the file name, without the dot.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// let url = Parser::new(None).parse("https://example.com/files/archive.tar.gz").unwrap();
+    /// assert_eq!(url.extension(), Some("gz".to_string()));
+    /// ```
+    pub fn extension(&self) -> Option<String> {
+        let name = self.file_name()?;
+        Self::split_extension(&name).map(|(_, extension)| extension.to_string())
+    }
+
+    /// Replace the extension of the file name, or remove it if `extension` is empty.
+    /// Returns `false` if the path has no file name.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// let mut url = Parser::new(None).parse("https://example.com/files/data.json").unwrap();
+    /// assert!(url.set_extension("csv"));
+    /// assert_eq!(url.file_name(), Some("data.csv".to_string()));
+    /// ```
+    pub fn set_extension(&mut self, extension: &str) -> bool {
+        let stem = match self.file_stem() {
+            Some(v) => v,
+            None => return false,
+        };
+        match extension.is_empty() {
+            true => self.set_file_name(&stem),
+            false => self.set_file_name(&format!("{stem}.{extension}")),
+        }
+        true
+    }
+
+    /// Create a copy of this URL with another path. Characters not allowed in a path are percent-encoded,
+    /// existing escapes are kept.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// let url = Parser::new(None).parse("https://example.com/old?q=1").unwrap();
+    /// let result = url.with_path("/new path/%C3%A9/");
+    /// assert_eq!(result.serialize(), "https://example.com/new%20path/%C3%A9/?q=1");
+    /// ```
+    pub fn with_path(&self, path: &str) -> Url {
+        let path = path.strip_prefix('/').unwrap_or(path);
+        let mut result = self.clone();
+        result.path = Some(
+            path.split('/')
+                .map(|segment| encode(segment, EncodeSet::Path))
+                .collect(),
+        );
+        result
+    }
+
+    fn split_extension(name: &str) -> Option<(&str, &str)> {
+        match name.rfind('.') {
+            Some(0) | None => None,
+            Some(position) => Some((&name[..position], &name[position + 1..])),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Parser;
+
+    #[test]
+    fn test_remove_dot_segments_works_when_rfc_examples() {
+        assert_eq!(remove_dot_segments("/a/b/c/./../../g"), "/a/g");
+        assert_eq!(remove_dot_segments("mid/content=5/../6"), "mid/6");
+    }
+
+    #[test]
+    fn test_remove_dot_segments_works_when_rfc_reference_resolution() {
+        // Merged paths of the RFC 3986 section 5.4 examples, base "http://a/b/c/d;p?q".
+        let vectors = [
+            ("/b/c/g", "/b/c/g"),
+            ("/b/c/./g", "/b/c/g"),
+            ("/b/c/g/", "/b/c/g/"),
+            ("/b/c/.", "/b/c/"),
+            ("/b/c/./", "/b/c/"),
+            ("/b/c/..", "/b/"),
+            ("/b/c/../", "/b/"),
+            ("/b/c/../g", "/b/g"),
+            ("/b/c/../..", "/"),
+            ("/b/c/../../", "/"),
+            ("/b/c/../../g", "/g"),
+            ("/b/c/../../../g", "/g"),
+            ("/b/c/../../../../g", "/g"),
+            ("/./g", "/g"),
+            ("/../g", "/g"),
+            ("/b/c/g.", "/b/c/g."),
+            ("/b/c/.g", "/b/c/.g"),
+            ("/b/c/g..", "/b/c/g.."),
+            ("/b/c/..g", "/b/c/..g"),
+            ("/b/c/./../g", "/b/g"),
+            ("/b/c/./g/.", "/b/c/g/"),
+            ("/b/c/g/./h", "/b/c/g/h"),
+            ("/b/c/g/../h", "/b/c/h"),
+            ("/b/c/g;x=1/./y", "/b/c/g;x=1/y"),
+            ("/b/c/g;x=1/../y", "/b/c/y"),
+        ];
+        for (input, expected) in vectors {
+            assert_eq!(remove_dot_segments(input), expected, "{input}");
+        }
+    }
+
+    #[test]
+    fn test_remove_dot_segments_works_when_encoded_dots() {
+        assert_eq!(remove_dot_segments("/a/%2e/b"), "/a/b");
+        assert_eq!(remove_dot_segments("/a/b/%2E%2e/c"), "/a/c");
+        assert_eq!(remove_dot_segments("/a/b/.%2E/c"), "/a/c");
+        assert_eq!(remove_dot_segments("/a/b/%2e./c"), "/a/c");
+        assert_eq!(remove_dot_segments("/a/%2e%2e%2e/c"), "/a/%2e%2e%2e/c");
+    }
+
+    #[test]
+    fn test_push_segment_works_when_no_path() {
+        let mut url = Url::empty();
+        url.push_segment("a");
+        url.push_segment("");
+        url.push_segment("b");
+        assert_eq!(url.path_segments().unwrap(), vec!["a", "b"]);
+    }
+
+    #[test]
+    fn test_pop_segment_works_when_single_segment() {
+        let mut url = Parser::new(None).parse("https://example.com/a").unwrap();
+        assert_eq!(url.pop_segment(), Some("a".to_string()));
+        assert_eq!(url.path_segments().unwrap(), vec![""]);
+        assert_eq!(url.pop_segment(), None);
+        assert_eq!(url.path_segments().unwrap(), vec![""]);
+    }
+
+    #[test]
+    fn test_file_name_is_none_when_trailing_slash() {
+        let url = Parser::new(None).parse("https://example.com/dir/").unwrap();
+        assert_eq!(url.file_name(), None);
+        assert_eq!(url.extension(), None);
+    }
+
+    #[test]
+    fn test_set_file_name_works_when_trailing_slash() {
+        let mut url = Parser::new(None).parse("https://example.com/dir/").unwrap();
+        url.set_file_name("index.html");
+        assert_eq!(url.path_segments().unwrap(), vec!["dir", "index.html"]);
+    }
+
+    #[test]
+    fn test_extension_works_when_hidden_file() {
+        let url = Parser::new(None)
+            .parse("https://example.com/.bashrc")
+            .unwrap();
+        assert_eq!(url.file_stem(), Some(".bashrc".to_string()));
+        assert_eq!(url.extension(), None);
+    }
+
+    #[test]
+    fn test_set_extension_works_when_removing() {
+        let mut url = Parser::new(None)
+            .parse("https://example.com/a/file.tar.gz")
+            .unwrap();
+        assert!(url.set_extension(""));
+        assert_eq!(url.file_name(), Some("file.tar".to_string()));
+
+        let mut url = Parser::new(None).parse("https://example.com/a/").unwrap();
+        assert!(!url.set_extension("txt"));
+    }
+
+    #[test]
+    fn test_normalize_path_works_when_typical() {
+        let mut url = Parser::new(None)
+            .parse("https://example.com/a/b/../c/./d/..")
+            .unwrap();
+        url.normalize_path();
+        assert_eq!(url.path_segments().unwrap(), vec!["a", "c", ""]);
+    }
+}
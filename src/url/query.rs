@@ -0,0 +1,334 @@
+use crate::encoding::{decode_form, encode, EncodeSet};
+use crate::url::Url;
+use std::str::FromStr;
+
+/// The decoded `key=value` pairs of a query, in order and with repeated keys preserved.
+///
+/// Pairs are separated by `&`; `+` and percent-escapes are decoded as in `application/x-www-form-urlencoded`.
+/// A pair without `=` has an empty value and empty pairs are skipped.
+///
+/// # Example
+/// ```rust
+/// use url_parse::url::QueryPairs;
+/// let query = QueryPairs::parse("tag=rust&tag=url&q=a+b%26c&page=2&flag");
+/// assert_eq!(query.get("q"), Some("a b&c"));
+/// assert_eq!(query.get_all("tag"), vec!["rust", "url"]);
+/// assert_eq!(query.get_parsed::<u32>("page"), Some(Ok(2)));
+/// assert!(query.contains_key("flag"));
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct QueryPairs {
+    pub pairs: Vec<(String, String)>,
+}
+
+impl QueryPairs {
+    /// Parse a raw query, without the leading `?`.
+    pub fn parse(query: &str) -> Self {
+        let pairs = query
+            .split('&')
+            .filter(|pair| !pair.is_empty())
+            .map(decode_pair)
+            .collect();
+        Self { pairs }
+    }
+
+    /// Get the value of the first pair with the given key.
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.pairs
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+    }
+
+    /// Get the values of all pairs with the given key, in order.
+    pub fn get_all(&self, key: &str) -> Vec<&str> {
+        self.pairs
+            .iter()
+            .filter(|(k, _)| k == key)
+            .map(|(_, v)| v.as_str())
+            .collect()
+    }
+
+    /// Parse the value of the first pair with the given key. `None` if the key is missing.
+    pub fn get_parsed<T: FromStr>(&self, key: &str) -> Option<Result<T, T::Err>> {
+        self.get(key).map(|v| v.parse::<T>())
+    }
+
+    /// Check whether a pair with the given key exists.
+    pub fn contains_key(&self, key: &str) -> bool {
+        self.pairs.iter().any(|(k, _)| k == key)
+    }
+
+    /// Iterate over the pairs in order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.pairs.iter().map(|(k, v)| (k.as_str(), v.as_str()))
+    }
+
+    /// The number of pairs.
+    pub fn len(&self) -> usize {
+        self.pairs.len()
+    }
+
+    /// Check whether there are no pairs.
+    pub fn is_empty(&self) -> bool {
+        self.pairs.is_empty()
+    }
+}
+
+/// Serialize the pairs as `application/x-www-form-urlencoded`.
+impl std::fmt::Display for QueryPairs {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        let pairs: Vec<String> = self.iter().map(|(k, v)| encode_pair(k, v)).collect();
+        write!(f, "{}", pairs.join("&"))
+    }
+}
+
+impl IntoIterator for QueryPairs {
+    type Item = (String, String);
+    type IntoIter = std::vec::IntoIter<(String, String)>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.pairs.into_iter()
+    }
+}
+
+impl Url {
+    /// Extract the decoded query pairs of the url. Empty if there is no query.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// let url = Parser::new(None).parse("https://example.com/search?q=rust+url&page=3").unwrap();
+    /// let query = url.query_pairs();
+    /// assert_eq!(query.get("q"), Some("rust url"));
+    /// assert_eq!(query.get_parsed::<u32>("page"), Some(Ok(3)));
+    /// ```
+    pub fn query_pairs(&self) -> QueryPairs {
+        QueryPairs::parse(self.query.as_deref().unwrap_or_default())
+    }
+
+    /// Append a pair to the query, after any existing pairs with the same key.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// let mut url = Parser::new(None).parse("https://example.com/search?q=rust").unwrap();
+    /// url.append_pair("tag", "a&b c");
+    /// assert_eq!(url.serialize(), "https://example.com/search?q=rust&tag=a%26b+c");
+    /// ```
+    pub fn append_pair(&mut self, key: &str, value: &str) {
+        let mut pairs = self.raw_pairs();
+        pairs.push(encode_pair(key, value));
+        self.set_raw_pairs(pairs);
+    }
+
+    /// Set the value of a key: the first pair with this key is replaced and the others are removed.
+    /// The pair is appended if the key is missing.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// let mut url = Parser::new(None).parse("https://example.com/?page=1&q=x&page=2").unwrap();
+    /// url.set_pair("page", "3");
+    /// assert_eq!(url.query, Some("page=3&q=x".to_string()));
+    /// ```
+    pub fn set_pair(&mut self, key: &str, value: &str) {
+        let mut found = false;
+        let mut pairs = vec![];
+        for pair in self.raw_pairs() {
+            match decoded_key(&pair) == key {
+                true if found => continue,
+                true => {
+                    found = true;
+                    pairs.push(encode_pair(key, value));
+                }
+                false => pairs.push(pair),
+            }
+        }
+        if !found {
+            pairs.push(encode_pair(key, value));
+        }
+        self.set_raw_pairs(pairs);
+    }
+
+    /// Remove all pairs with the given key. The query is removed when no pair is left.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// let mut url = Parser::new(None).parse("https://example.com/?utm_source=x&id=1&utm_source=y").unwrap();
+    /// url.remove_key("utm_source");
+    /// assert_eq!(url.serialize(), "https://example.com/?id=1");
+    /// ```
+    pub fn remove_key(&mut self, key: &str) {
+        self.retain(|k, _| k != key);
+    }
+
+    /// Keep only the pairs for which the predicate, called with the decoded key and value, returns `true`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// let mut url = Parser::new(None).parse("https://example.com/?utm_source=x&id=1&utm_medium=y").unwrap();
+    /// url.retain(|key, _| !key.starts_with("utm_"));
+    /// assert_eq!(url.query, Some("id=1".to_string()));
+    /// ```
+    pub fn retain(&mut self, mut predicate: impl FnMut(&str, &str) -> bool) {
+        let pairs = self
+            .raw_pairs()
+            .into_iter()
+            .filter(|pair| {
+                let (key, value) = decode_pair(pair);
+                predicate(&key, &value)
+            })
+            .collect();
+        self.set_raw_pairs(pairs);
+    }
+
+    /// Sort the pairs by decoded key. Pairs with the same key keep their order.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::core::Parser;
+    /// let mut url = Parser::new(None).parse("https://example.com/?b=2&a=1&b=1").unwrap();
+    /// url.sort_pairs();
+    /// assert_eq!(url.query, Some("a=1&b=2&b=1".to_string()));
+    /// ```
+    pub fn sort_pairs(&mut self) {
+        let mut pairs = self.raw_pairs();
+        pairs.sort_by_cached_key(|pair| decoded_key(pair));
+        self.set_raw_pairs(pairs);
+    }
+
+    /// Remove the query.
+    pub fn clear_query(&mut self) {
+        self.query = None;
+    }
+
+    /// The non-empty `&`-separated pairs of the query, as written.
+    fn raw_pairs(&self) -> Vec<String> {
+        self.query
+            .iter()
+            .flat_map(|query| query.split('&'))
+            .filter(|pair| !pair.is_empty())
+            .map(|pair| pair.to_string())
+            .collect()
+    }
+
+    fn set_raw_pairs(&mut self, pairs: Vec<String>) {
+        self.query = match pairs.is_empty() {
+            true => None,
+            false => Some(pairs.join("&")),
+        };
+    }
+}
+
+pub(crate) fn encode_pair(key: &str, value: &str) -> String {
+    format!(
+        "{}={}",
+        encode(key, EncodeSet::FormUrlencoded),
+        encode(value, EncodeSet::FormUrlencoded)
+    )
+}
+
+fn decode_pair(pair: &str) -> (String, String) {
+    match pair.split_once('=') {
+        Some((key, value)) => (decode_form(key), decode_form(value)),
+        None => (decode_form(pair), String::new()),
+    }
+}
+
+fn decoded_key(pair: &str) -> String {
+    decode_pair(pair).0
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_pairs_parse_works_when_repeated_keys() {
+        let result = QueryPairs::parse("a=1&b=2&a=3");
+        assert_eq!(
+            result.pairs,
+            vec![
+                ("a".to_string(), "1".to_string()),
+                ("b".to_string(), "2".to_string()),
+                ("a".to_string(), "3".to_string()),
+            ]
+        );
+        assert_eq!(result.get("a"), Some("1"));
+        assert_eq!(result.get_all("a"), vec!["1", "3"]);
+    }
+
+    #[test]
+    fn test_query_pairs_parse_works_when_encoded() {
+        let result = QueryPairs::parse("na%20me=J%C3%B6rg+M%2B&eq=a=b&&empty=&=v&%zz=%");
+        let pairs: Vec<(&str, &str)> = result.iter().collect();
+        assert_eq!(
+            pairs,
+            vec![
+                ("na me", "Jörg M+"),
+                ("eq", "a=b"),
+                ("empty", ""),
+                ("", "v"),
+                ("%zz", "%"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_get_parsed_works_when_invalid() {
+        let result = QueryPairs::parse("page=two");
+        assert!(result.get_parsed::<u32>("page").unwrap().is_err());
+        assert!(result.get_parsed::<u32>("size").is_none());
+    }
+
+    #[test]
+    fn test_display_works_when_roundtrip() {
+        let input = QueryPairs::parse("na%20me=J%C3%B6rg+M%2B&x=~");
+        assert_eq!(input.to_string(), "na+me=J%C3%B6rg+M%2B&x=%7E");
+        assert_eq!(QueryPairs::parse(&input.to_string()), input);
+    }
+
+    #[test]
+    fn test_append_pair_works_when_no_query() {
+        let mut url = Url::empty();
+        url.append_pair("a b", "ü=1");
+        assert_eq!(url.query, Some("a+b=%C3%BC%3D1".to_string()));
+        assert_eq!(url.query_pairs().get("a b"), Some("ü=1"));
+    }
+
+    #[test]
+    fn test_set_pair_works_when_encoded_key() {
+        let mut url = Url::empty();
+        url.query = Some("a%20b=1&keep=%7E&a+b=2".to_string());
+        url.set_pair("a b", "3");
+        assert_eq!(url.query, Some("a+b=3&keep=%7E".to_string()));
+    }
+
+    #[test]
+    fn test_remove_key_works_when_last_pair() {
+        let mut url = Url::empty();
+        url.query = Some("a=1&&a".to_string());
+        url.remove_key("a");
+        assert_eq!(url.query, None);
+    }
+
+    #[test]
+    fn test_clear_query_works_when_typical() {
+        let mut url = Url::empty();
+        url.query = Some("a=1".to_string());
+        url.anchor = Some("top".to_string());
+        url.clear_query();
+        assert_eq!(url.serialize(), "#top");
+    }
+
+    #[test]
+    fn test_query_pairs_is_empty_when_no_query() {
+        let result = Url::empty().query_pairs();
+        assert!(result.is_empty());
+        assert_eq!(result.len(), 0);
+        assert!(!result.contains_key(""));
+    }
+}
@@ -0,0 +1,773 @@
+use crate::error::QueryError;
+use crate::url::query::encode_pair;
+use crate::url::Url;
+use serde::de::{self, DeserializeOwned, IntoDeserializer};
+use serde::ser::{self, Impossible, Serialize};
+
+impl Url {
+    /// Deserialize the query pairs into a struct or map. Repeated keys fill `Vec` fields, a missing key leaves
+    /// an `Option` field `None` and other fields take the last value of their key.
+    ///
+    /// # Example
+    /// ```rust
+    /// use serde::Deserialize;
+    /// use url_parse::core::Parser;
+    ///
+    /// #[derive(Deserialize, Debug, PartialEq)]
+    /// #[serde(rename_all = "lowercase")]
+    /// enum Order {
+    ///     Asc,
+    ///     Desc,
+    /// }
+    ///
+    /// #[derive(Deserialize, Debug, PartialEq)]
+    /// struct SearchParams {
+    ///     q: String,
+    ///     page: u32,
+    ///     tag: Vec<String>,
+    ///     order: Option<Order>,
+    /// }
+    ///
+    /// let url = Parser::new(None).parse("https://example.com/?q=rust+url&page=2&tag=a&tag=b").unwrap();
+    /// let params: SearchParams = url.query_as().unwrap();
+    /// assert_eq!(params.q, "rust url");
+    /// assert_eq!(params.tag, vec!["a", "b"]);
+    /// assert_eq!(params.order, None);
+    ///
+    /// let url = Parser::new(None).parse("https://example.com/?q=x&page=two").unwrap();
+    /// let error = url.query_as::<SearchParams>().unwrap_err();
+    /// assert_eq!(error.key, Some("page".to_string()));
+    /// ```
+    pub fn query_as<T: DeserializeOwned>(&self) -> Result<T, QueryError> {
+        let mut groups: Vec<(String, Vec<String>)> = vec![];
+        for (key, value) in self.query_pairs() {
+            match groups.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, values)) => values.push(value),
+                None => groups.push((key, vec![value])),
+            }
+        }
+        T::deserialize(QueryDeserializer {
+            groups: groups.into_iter(),
+            value: None,
+        })
+    }
+
+    /// Create a copy of this URL with the query replaced by the serialized struct or map.
+    /// `None` fields are left out and sequences become repeated keys.
+    ///
+    /// # Example
+    /// ```rust
+    /// use serde::Serialize;
+    /// use url_parse::core::Parser;
+    ///
+    /// #[derive(Serialize)]
+    /// struct SearchParams {
+    ///     q: String,
+    ///     page: u32,
+    ///     tag: Vec<&'static str>,
+    ///     lang: Option<String>,
+    /// }
+    ///
+    /// let url = Parser::new(None).parse("https://example.com/search").unwrap();
+    /// let params = SearchParams { q: "a&b".to_string(), page: 1, tag: vec!["x", "y"], lang: None };
+    /// let result = url.with_query_from(&params).unwrap();
+    /// assert_eq!(result.serialize(), "https://example.com/search?q=a%26b&page=1&tag=x&tag=y");
+    /// ```
+    pub fn with_query_from<T: Serialize>(&self, value: &T) -> Result<Url, QueryError> {
+        let mut pairs = vec![];
+        value.serialize(QuerySerializer { pairs: &mut pairs })?;
+        let mut result = self.clone();
+        result.query = match pairs.is_empty() {
+            true => None,
+            false => Some(pairs.join("&")),
+        };
+        Ok(result)
+    }
+}
+
+impl de::Error for QueryError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        QueryError {
+            key: None,
+            message: msg.to_string(),
+        }
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        Self::custom(format_args!("missing field `{field}`")).with_key(field)
+    }
+
+    fn unknown_field(field: &str, expected: &'static [&'static str]) -> Self {
+        let expected: Vec<String> = expected.iter().map(|f| format!("`{f}`")).collect();
+        let message = match expected.is_empty() {
+            true => format!("unknown field `{field}`, there are no fields"),
+            false => format!(
+                "unknown field `{field}`, expected one of {}",
+                expected.join(", ")
+            ),
+        };
+        Self::custom(message).with_key(field)
+    }
+}
+
+impl ser::Error for QueryError {
+    fn custom<T: std::fmt::Display>(msg: T) -> Self {
+        <QueryError as de::Error>::custom(msg)
+    }
+}
+
+impl QueryError {
+    fn with_key(mut self, key: &str) -> Self {
+        self.key.get_or_insert_with(|| key.to_string());
+        self
+    }
+}
+
+/// Deserializes the top level, a map from keys to their values.
+struct QueryDeserializer {
+    groups: std::vec::IntoIter<(String, Vec<String>)>,
+    value: Option<(String, Vec<String>)>,
+}
+
+impl<'de> de::Deserializer<'de> for QueryDeserializer {
+    type Error = QueryError;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, QueryError> {
+        visitor.visit_map(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de> de::MapAccess<'de> for QueryDeserializer {
+    type Error = QueryError;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, QueryError> {
+        match self.groups.next() {
+            Some((key, values)) => {
+                let result = seed.deserialize(key.as_str().into_deserializer());
+                self.value = Some((key, values));
+                result.map(Some)
+            }
+            None => Ok(None),
+        }
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: V,
+    ) -> Result<V::Value, QueryError> {
+        let (key, values) = self
+            .value
+            .take()
+            .ok_or_else(|| <QueryError as de::Error>::custom("value requested before key"))?;
+        seed.deserialize(ValueDeserializer {
+            values: values.clone(),
+        })
+        .map_err(|e| e.with_key(&key))
+    }
+}
+
+/// Deserializes the values of one key.
+struct ValueDeserializer {
+    values: Vec<String>,
+}
+
+impl ValueDeserializer {
+    fn last(&self) -> &str {
+        self.values.last().map(|v| v.as_str()).unwrap_or_default()
+    }
+
+    fn parse<T>(&self) -> Result<T, QueryError>
+    where
+        T: std::str::FromStr,
+        T::Err: std::fmt::Display,
+    {
+        self.last().parse::<T>().map_err(|e| {
+            <QueryError as de::Error>::custom(format!("invalid value '{}': {e}", self.last()))
+        })
+    }
+}
+
+macro_rules! deserialize_parsed {
+    ($($method:ident => $visit:ident,)*) => {
+        $(
+            fn $method<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, QueryError> {
+                visitor.$visit(self.parse()?)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer {
+    type Error = QueryError;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, QueryError> {
+        match self.values.len() {
+            1 => visitor.visit_string(self.last().to_string()),
+            _ => self.deserialize_seq(visitor),
+        }
+    }
+
+    deserialize_parsed! {
+        deserialize_bool => visit_bool,
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_str<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, QueryError> {
+        visitor.visit_string(self.last().to_string())
+    }
+
+    fn deserialize_string<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, QueryError> {
+        visitor.visit_string(self.last().to_string())
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, QueryError> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, QueryError> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, QueryError> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, QueryError> {
+        let values = self
+            .values
+            .into_iter()
+            .map(|v| ValueDeserializer { values: vec![v] });
+        visitor.visit_seq(de::value::SeqDeserializer::new(values))
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, QueryError> {
+        visitor.visit_enum(self.last().to_string().into_deserializer())
+    }
+
+    serde::forward_to_deserialize_any! {
+        i128 u128 bytes byte_buf unit_struct tuple tuple_struct map struct identifier ignored_any
+    }
+}
+
+impl<'de> IntoDeserializer<'de, QueryError> for ValueDeserializer {
+    type Deserializer = Self;
+
+    fn into_deserializer(self) -> Self {
+        self
+    }
+}
+
+/// Serializes the top level, a struct or a map.
+struct QuerySerializer<'a> {
+    pairs: &'a mut Vec<String>,
+}
+
+fn unsupported(what: &str) -> QueryError {
+    <QueryError as ser::Error>::custom(format!("{what} cannot be serialized to a query"))
+}
+
+macro_rules! serialize_unsupported {
+    ($what:expr; $($method:ident($($arg:ty),*),)*) => {
+        $(
+            fn $method(self, $(_: $arg),*) -> Result<Self::Ok, QueryError> {
+                Err(unsupported($what))
+            }
+        )*
+    };
+}
+
+impl<'a> ser::Serializer for QuerySerializer<'a> {
+    type Ok = ();
+    type Error = QueryError;
+    type SerializeSeq = Impossible<(), QueryError>;
+    type SerializeTuple = Impossible<(), QueryError>;
+    type SerializeTupleStruct = Impossible<(), QueryError>;
+    type SerializeTupleVariant = Impossible<(), QueryError>;
+    type SerializeMap = QueryMapSerializer<'a>;
+    type SerializeStruct = QueryMapSerializer<'a>;
+    type SerializeStructVariant = Impossible<(), QueryError>;
+
+    serialize_unsupported! { "a top-level value";
+        serialize_bool(bool), serialize_i8(i8), serialize_i16(i16), serialize_i32(i32), serialize_i64(i64),
+        serialize_u8(u8), serialize_u16(u16), serialize_u32(u32), serialize_u64(u64),
+        serialize_f32(f32), serialize_f64(f64), serialize_char(char), serialize_str(&str),
+        serialize_bytes(&[u8]), serialize_unit_struct(&'static str),
+        serialize_unit_variant(&'static str, u32, &'static str),
+    }
+
+    fn serialize_none(self) -> Result<(), QueryError> {
+        Ok(())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), QueryError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), QueryError> {
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), QueryError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), QueryError> {
+        Err(unsupported("a top-level enum"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, QueryError> {
+        Err(unsupported("a top-level sequence"))
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self::SerializeTuple, QueryError> {
+        Err(unsupported("a top-level tuple"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, QueryError> {
+        Err(unsupported("a top-level tuple"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, QueryError> {
+        Err(unsupported("a top-level enum"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, QueryError> {
+        Ok(QueryMapSerializer {
+            pairs: self.pairs,
+            key: None,
+        })
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, QueryError> {
+        self.serialize_map(None)
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, QueryError> {
+        Err(unsupported("a top-level enum"))
+    }
+}
+
+struct QueryMapSerializer<'a> {
+    pairs: &'a mut Vec<String>,
+    key: Option<String>,
+}
+
+impl QueryMapSerializer<'_> {
+    fn field<T: ?Sized + Serialize>(&mut self, key: &str, value: &T) -> Result<(), QueryError> {
+        value
+            .serialize(ValueSerializer {
+                key,
+                pairs: self.pairs,
+                nested: false,
+            })
+            .map_err(|e| e.with_key(key))
+    }
+}
+
+impl ser::SerializeMap for QueryMapSerializer<'_> {
+    type Ok = ();
+    type Error = QueryError;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), QueryError> {
+        let mut pairs = vec![];
+        key.serialize(ValueSerializer {
+            key: "",
+            pairs: &mut pairs,
+            nested: true,
+        })?;
+        let key = pairs.pop().ok_or_else(|| unsupported("a missing key"))?;
+        self.key = Some(crate::encoding::decode_form(&key[1..]));
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), QueryError> {
+        let key = self.key.take().unwrap_or_default();
+        self.field(&key, value)
+    }
+
+    fn end(self) -> Result<(), QueryError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeStruct for QueryMapSerializer<'_> {
+    type Ok = ();
+    type Error = QueryError;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), QueryError> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), QueryError> {
+        Ok(())
+    }
+}
+
+/// Serializes the value of one key to one pair per scalar.
+struct ValueSerializer<'a> {
+    key: &'a str,
+    pairs: &'a mut Vec<String>,
+    /// Set inside sequences, which cannot be nested.
+    nested: bool,
+}
+
+impl ValueSerializer<'_> {
+    fn push(self, value: &str) -> Result<(), QueryError> {
+        self.pairs.push(encode_pair(self.key, value));
+        Ok(())
+    }
+}
+
+macro_rules! serialize_display {
+    ($($method:ident($ty:ty),)*) => {
+        $(
+            fn $method(self, v: $ty) -> Result<(), QueryError> {
+                self.push(&v.to_string())
+            }
+        )*
+    };
+}
+
+impl<'a> ser::Serializer for ValueSerializer<'a> {
+    type Ok = ();
+    type Error = QueryError;
+    type SerializeSeq = ValueSeqSerializer<'a>;
+    type SerializeTuple = ValueSeqSerializer<'a>;
+    type SerializeTupleStruct = Impossible<(), QueryError>;
+    type SerializeTupleVariant = Impossible<(), QueryError>;
+    type SerializeMap = Impossible<(), QueryError>;
+    type SerializeStruct = Impossible<(), QueryError>;
+    type SerializeStructVariant = Impossible<(), QueryError>;
+
+    serialize_display! {
+        serialize_bool(bool), serialize_i8(i8), serialize_i16(i16), serialize_i32(i32), serialize_i64(i64),
+        serialize_u8(u8), serialize_u16(u16), serialize_u32(u32), serialize_u64(u64),
+        serialize_f32(f32), serialize_f64(f64), serialize_char(char), serialize_str(&str),
+    }
+
+    serialize_unsupported! { "bytes"; serialize_bytes(&[u8]), }
+
+    fn serialize_none(self) -> Result<(), QueryError> {
+        Ok(())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), QueryError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), QueryError> {
+        self.push("")
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), QueryError> {
+        self.push("")
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        variant: &'static str,
+    ) -> Result<(), QueryError> {
+        self.push(variant)
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), QueryError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), QueryError> {
+        Err(unsupported("an enum with data"))
+    }
+
+    fn serialize_seq(self, _len: Option<usize>) -> Result<Self::SerializeSeq, QueryError> {
+        match self.nested {
+            true => Err(unsupported("a nested sequence")),
+            false => Ok(ValueSeqSerializer {
+                key: self.key,
+                pairs: self.pairs,
+            }),
+        }
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self::SerializeTuple, QueryError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, QueryError> {
+        Err(unsupported("a nested tuple"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, QueryError> {
+        Err(unsupported("an enum with data"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<Self::SerializeMap, QueryError> {
+        Err(unsupported("a nested map"))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, QueryError> {
+        Err(unsupported("a nested struct"))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, QueryError> {
+        Err(unsupported("an enum with data"))
+    }
+}
+
+struct ValueSeqSerializer<'a> {
+    key: &'a str,
+    pairs: &'a mut Vec<String>,
+}
+
+impl ser::SerializeSeq for ValueSeqSerializer<'_> {
+    type Ok = ();
+    type Error = QueryError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), QueryError> {
+        value.serialize(ValueSerializer {
+            key: self.key,
+            pairs: self.pairs,
+            nested: true,
+        })
+    }
+
+    fn end(self) -> Result<(), QueryError> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeTuple for ValueSeqSerializer<'_> {
+    type Ok = ();
+    type Error = QueryError;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), QueryError> {
+        ser::SerializeSeq::serialize_element(self, value)
+    }
+
+    fn end(self) -> Result<(), QueryError> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Parser;
+    use serde::{Deserialize, Serialize};
+    use std::collections::BTreeMap;
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    #[serde(rename_all = "snake_case")]
+    enum Kind {
+        Issue,
+        PullRequest,
+    }
+
+    #[derive(Deserialize, Serialize, Debug, PartialEq)]
+    struct Params {
+        q: String,
+        page: Option<u16>,
+        ratio: f64,
+        draft: bool,
+        kind: Kind,
+        ids: Vec<u32>,
+        #[serde(default)]
+        labels: Vec<String>,
+    }
+
+    #[test]
+    fn test_query_as_works_when_typical() {
+        let url = Parser::new(None)
+            .parse(
+                "https://x.com/?q=a%20b&ratio=0.5&draft=true&kind=pull_request&ids=3&ids=1&extra=1",
+            )
+            .unwrap();
+        let result: Params = url.query_as().unwrap();
+        assert_eq!(
+            result,
+            Params {
+                q: "a b".to_string(),
+                page: None,
+                ratio: 0.5,
+                draft: true,
+                kind: Kind::PullRequest,
+                ids: vec![3, 1],
+                labels: vec![],
+            }
+        );
+    }
+
+    #[test]
+    fn test_query_as_fails_with_key_when_invalid() {
+        let url = Parser::new(None)
+            .parse("https://x.com/?q=a&ratio=1&draft=true&kind=issue&ids=1&ids=x")
+            .unwrap();
+        let result = url.query_as::<Params>().unwrap_err();
+        assert_eq!(result.key, Some("ids".to_string()));
+
+        let url = Parser::new(None)
+            .parse("https://x.com/?q=a&ratio=1&draft=true&kind=epic&ids=1")
+            .unwrap();
+        let result = url.query_as::<Params>().unwrap_err();
+        assert_eq!(result.key, Some("kind".to_string()));
+
+        let url = Parser::new(None).parse("https://x.com/?q=a").unwrap();
+        let result = url.query_as::<Params>().unwrap_err();
+        assert_eq!(result.key, Some("ratio".to_string()));
+        assert!(result.to_string().contains("missing field `ratio`"));
+    }
+
+    #[test]
+    fn test_query_as_fails_with_key_when_unknown_field() {
+        #[derive(Deserialize, Debug)]
+        #[serde(deny_unknown_fields)]
+        #[allow(dead_code)]
+        struct Strict {
+            q: String,
+        }
+        let url = Parser::new(None)
+            .parse("https://x.com/?q=a&extra=1")
+            .unwrap();
+        let result = url.query_as::<Strict>().unwrap_err();
+        assert_eq!(result.key, Some("extra".to_string()));
+        assert!(result.to_string().contains("expected one of `q`"));
+    }
+
+    #[test]
+    fn test_query_as_works_when_map() {
+        let url = Parser::new(None).parse("https://x.com/?b=2&a=1").unwrap();
+        let result: BTreeMap<String, String> = url.query_as().unwrap();
+        assert_eq!(result["a"], "1");
+        assert_eq!(result["b"], "2");
+    }
+
+    #[test]
+    fn test_with_query_from_works_when_roundtrip() {
+        let params = Params {
+            q: "ä & ö".to_string(),
+            page: Some(3),
+            ratio: 1.5,
+            draft: false,
+            kind: Kind::Issue,
+            ids: vec![7, 8],
+            labels: vec![],
+        };
+        let url = Parser::new(None)
+            .parse("https://x.com/search?old=1#top")
+            .unwrap()
+            .with_query_from(&params)
+            .unwrap();
+        assert_eq!(
+            url.serialize(),
+            "https://x.com/search?q=%C3%A4+%26+%C3%B6&page=3&ratio=1.5&draft=false&kind=issue&ids=7&ids=8#top"
+        );
+        assert_eq!(url.query_as::<Params>().unwrap(), params);
+    }
+
+    #[test]
+    fn test_with_query_from_fails_when_nested() {
+        #[derive(Serialize)]
+        struct Outer {
+            inner: BTreeMap<String, String>,
+        }
+        let value = Outer {
+            inner: BTreeMap::new(),
+        };
+        let result = Url::empty().with_query_from(&value).unwrap_err();
+        assert_eq!(result.key, Some("inner".to_string()));
+        assert!(Url::empty().with_query_from(&vec![1]).is_err());
+    }
+}
@@ -1,6 +1,7 @@
 use crate::core::scheme_separator::SchemeSeparator;
 use crate::core::Parser;
 use std::collections::HashMap;
+use std::net::Ipv4Addr;
 pub struct Utils;
 
 impl Utils {
@@ -28,7 +29,7 @@ impl Utils {
         }
     }
 
-    /// Get substring immediately after login. Eliminates scheme to ensure no colon present in remainder.
+    /// Get substring immediately after login, which ends at the last `@` of the authority. Eliminates scheme to ensure no colon present in remainder.
     ///
     /// # Example
     /// ```rust
@@ -43,12 +44,25 @@ impl Utils {
     /// ```
     pub fn substring_after_login<'a>(parser: &Parser, input: &'a str) -> &'a str {
         let input = Utils::substring_after_scheme(parser, input);
-        match input.find('@') {
+        match input[..Utils::authority_end(input)].rfind('@') {
             Some(pos) => &input[pos + 1..],
             None => input,
         }
     }
 
+    /// Get the position where the authority (login, host and port) ends in a string starting after the scheme.
+    ///
+    /// # Example
+    /// ```rust
+    /// use url_parse::utils::Utils;
+    /// let input = "user:pass@www.example.co.uk:443/blog/article/search?docid=720&hl=en#dayone";
+    /// let result = Utils::authority_end(input);
+    /// assert_eq!(&input[..result], "user:pass@www.example.co.uk:443");
+    /// ```
+    pub fn authority_end(input: &str) -> usize {
+        input.find(['/', '?', '#']).unwrap_or(input.len())
+    }
+
     /// Get substring immediately after port. Eliminates scheme to ensure no colon present in remainder.
     ///
     /// # Example
@@ -64,18 +78,10 @@ impl Utils {
     /// ```
     pub fn substring_after_port<'a>(parser: &Parser, input: &'a str) -> &'a str {
         let input = Utils::substring_after_scheme(parser, input);
-        let port = parser.port(input);
-
-        if input.find(':').is_some() {
-            let (pos_port, len_port_string) = match port {
-                Some(v) => (input.find(&v.to_string()).unwrap(), v.to_string().len() + 1),
-                None => (0, 0),
-            };
-
-            let substring_after_port = input.get(pos_port + len_port_string..);
-            return substring_after_port.unwrap_or_default();
+        match parser.port_span(input) {
+            Some((_, end)) => input.get(end + 1..).unwrap_or_default(),
+            None => input,
         }
-        input
     }
 
     /// Get substring immediately before port.
@@ -91,14 +97,10 @@ impl Utils {
     /// assert_eq!(result, expected);
     /// ```
     pub fn substring_before_port<'a>(parser: &Parser, input: &'a str) -> &'a str {
-        let port = parser.port(input);
-
-        let pos_port = match port {
-            Some(v) => input.find(&v.to_string()).unwrap() - 1,
-            None => input.len(),
-        };
-
-        return input.get(..pos_port).unwrap();
+        match parser.port_span(input) {
+            Some((start, _)) => &input[..start - 1],
+            None => input,
+        }
     }
 
     /// Get substring starting at path field. Eliminates scheme to ensure no colon present in remainder.
@@ -117,9 +119,10 @@ impl Utils {
     /// ```
     pub fn substring_from_path_begin<'a>(parser: &Parser, input: &'a str) -> Option<&'a str> {
         let input = Utils::substring_after_scheme(parser, input);
-        match input.find('/') {
-            Some(pos) => Some(&input[pos..]),
-            None => None,
+        let input = &input[Utils::authority_end(input)..];
+        match input.starts_with('/') {
+            true => Some(input),
+            false => None,
         }
     }
 
@@ -152,6 +155,55 @@ impl Utils {
         result
     }
 
+    /// Parse an IPv4 address written with one to four dot-separated decimal, octal (`0` prefix) or hex (`0x` prefix)
+    /// parts, as browsers accept it. The last part fills the remaining bytes.
+    ///
+    /// # Example
+    /// ```rust
+    /// use std::net::Ipv4Addr;
+    /// use url_parse::utils::Utils;
+    /// let expected = Some(Ipv4Addr::new(127, 0, 0, 1));
+    /// assert_eq!(Utils::parse_ipv4("127.1"), expected);
+    /// assert_eq!(Utils::parse_ipv4("2130706433"), expected);
+    /// assert_eq!(Utils::parse_ipv4("0x7f.0.0.1"), expected);
+    /// assert_eq!(Utils::parse_ipv4("0177.0.0.1"), expected);
+    /// assert_eq!(Utils::parse_ipv4("example.com"), None);
+    /// ```
+    pub fn parse_ipv4(host: &str) -> Option<Ipv4Addr> {
+        let parts: Vec<&str> = host.split('.').collect();
+        if parts.len() > 4 {
+            return None;
+        }
+        let numbers = parts
+            .iter()
+            .map(|part| Self::parse_ipv4_number(part))
+            .collect::<Option<Vec<u64>>>()?;
+        let (last, init) = numbers.split_last()?;
+        if init.iter().any(|n| *n > 255) || *last >= 1 << (8 * (5 - numbers.len())) {
+            return None;
+        }
+        let value = init
+            .iter()
+            .enumerate()
+            .fold(*last, |acc, (i, n)| acc + (n << (8 * (3 - i))));
+        Some(Ipv4Addr::from(value as u32))
+    }
+
+    fn parse_ipv4_number(part: &str) -> Option<u64> {
+        let (digits, radix) = match part.strip_prefix("0x").or(part.strip_prefix("0X")) {
+            Some(hex) => (hex, 16),
+            None if part.len() > 1 && part.starts_with('0') => (&part[1..], 8),
+            None => (part, 10),
+        };
+        if digits.is_empty() {
+            return (radix == 16).then_some(0);
+        }
+        if !digits.chars().all(|c| c.is_digit(radix)) {
+            return None;
+        }
+        u64::from_str_radix(digits, radix).ok()
+    }
+
     fn compute_similarity<'a>(
         parser: &Parser,
         input: &'a str,
@@ -250,6 +302,24 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_substring_after_login_works_when_at_in_password() {
+        let input = "https://user:p@ss@www.example.co.uk:443/blog/@me";
+        let expected = "www.example.co.uk:443/blog/@me".to_string();
+        let parser = Parser::new(None);
+        let result = Utils::substring_after_login(&parser, input);
+        assert_eq!(result, expected);
+    }
+
+    #[test]
+    fn test_substring_after_login_works_when_at_in_path() {
+        let input = "https://www.example.co.uk/blog/@me";
+        let expected = "www.example.co.uk/blog/@me".to_string();
+        let parser = Parser::new(None);
+        let result = Utils::substring_after_login(&parser, input);
+        assert_eq!(result, expected);
+    }
+
     #[test]
     fn test_substring_from_path_begin_works_when_typical() {
         let input = "https://www.example.co.uk:443/blog/article/search?docid=720&hl=en#dayone";
@@ -268,6 +338,14 @@ mod tests {
         assert_eq!(result, expected);
     }
 
+    #[test]
+    fn test_substring_from_path_begin_is_none_when_no_path() {
+        let input = "https://www.example.co.uk?redirect=/blog#/top";
+        let parser = Parser::new(None);
+        let result = Utils::substring_from_path_begin(&parser, input);
+        assert!(result.is_none());
+    }
+
     #[test]
     fn test_substring_after_port_works_when_colon_in_url() {
         let input = "http://en.wikipedia.org/wiki/Template:Welcome";